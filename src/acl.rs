@@ -0,0 +1,336 @@
+use std::{
+    collections::HashMap,
+    sync::LazyLock,
+    time::Duration
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::Deserialize;
+
+use crate::{
+    config::AclConfig,
+    http::HttpClient,
+    model::{ArxivResult, Author, DateRange, Source},
+    parser::ParserError,
+    source::{clean, previous_day, require_text, skip_malformed, InlineMarkup, PaperSource}
+};
+
+// ACL Anthology volumes, read from the collection XML files the Anthology is
+// built from. See https://github.com/acl-org/acl-anthology/tree/master/data/xml
+//
+// Volumes are dated by the day they were ingested, a daily run picks up the
+// proceedings that were added to the Anthology that day.
+
+const ACL_DATA_URL: &str = "https://raw.githubusercontent.com/acl-org/acl-anthology/master/data/xml";
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+// elements with inline markup such as `<fixed-case>` or `<tex-math>`.
+static MARKUP: LazyLock<InlineMarkup> = LazyLock::new(|| InlineMarkup::new(&["title", "booktitle", "abstract"]));
+
+#[derive(Debug)]
+pub struct AclAnthology {
+    config: AclConfig,
+    client: HttpClient
+}
+
+impl AclAnthology {
+    pub fn from_config(config: AclConfig) -> Self {
+        AclAnthology {
+            client: HttpClient::new(REQUEST_INTERVAL, config.retry.clone()),
+            config
+        }
+    }
+
+    async fn get_collection(&self, collection: &str) -> Result<AclCollection, ParserError> {
+        let url = format!("{}/{}.xml", ACL_DATA_URL, collection);
+        println!("using acl url: {}", url);
+        let xml = self.client.get(&url).await?;
+        Ok(quick_xml::de::from_str(&MARKUP.strip(&xml))?)
+    }
+}
+
+#[async_trait]
+impl PaperSource for AclAnthology {
+    fn source(&self) -> Source {
+        Source::AclAnthology
+    }
+
+    fn default_range(&self) -> DateRange {
+        previous_day()
+    }
+
+    async fn fetch(&self, range: &DateRange) -> Result<Vec<ArxivResult>, ParserError> {
+        let mut results: Vec<ArxivResult> = Vec::new();
+        for name in &self.config.collections {
+            let collection = self.get_collection(name).await?;
+            for volume in &collection.volumes {
+                let ingested = volume.date()?;
+                if ingested < range.from || ingested >= range.to {
+                    continue;
+                }
+                println!("volume {}-{}, papers {}", collection.id, volume.id, volume.papers.len());
+                for paper in &volume.papers {
+                    // front matter and older papers come without an abstract.
+                    let result = require_text(paper.to_result(&collection.id, volume, ingested, results.len()));
+                    if let Some(result) = skip_malformed(result)? {
+                        results.push(result);
+                    }
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Papers by Anthology id, e.g. `2024.acl-long.1` or `P19-1001`.
+    async fn fetch_by_ids(&self, ids: &[&str]) -> Result<Vec<ArxivResult>, ParserError> {
+        let mut results: Vec<ArxivResult> = Vec::new();
+        let mut collections: HashMap<String, AclCollection> = HashMap::new();
+        for id in ids {
+            let Some((collection_id, volume_id, paper_id)) = split_anthology_id(id.trim()) else {
+                eprintln!("Warning: {} is not an Anthology id", id);
+                continue;
+            };
+            if !collections.contains_key(&collection_id) {
+                let collection = self.get_collection(&collection_id).await?;
+                collections.insert(collection_id.clone(), collection);
+            }
+            let collection = &collections[&collection_id];
+            let found = collection.volumes.iter()
+                .find(|volume| volume.id == volume_id)
+                .and_then(|volume| volume.papers.iter()
+                    .find(|paper| paper.id == paper_id)
+                    .map(|paper| (volume, paper)));
+            match found {
+                Some((volume, paper)) => {
+                    let ingested = volume.date()?;
+                    let result = require_text(paper.to_result(&collection.id, volume, ingested, results.len()));
+                    if let Some(result) = skip_malformed(result)? {
+                        results.push(result);
+                    }
+                },
+                None => eprintln!("Warning: {} not found", id)
+            }
+        }
+        Ok(results)
+    }
+}
+
+// old-style collections have ids like `P19`, newer ones `2024.acl`.
+fn anthology_id(collection: &str, volume: &str, paper: &str) -> String {
+    if collection.contains('.') {
+        format!("{}-{}.{}", collection, volume, paper)
+    } else {
+        format!("{}-{}{:0>3}", collection, volume, paper)
+    }
+}
+
+// `2024.acl-long.1` -> (`2024.acl`, `long`, `1`), `P19-1001` -> (`P19`, `1`, `1`)
+fn split_anthology_id(id: &str) -> Option<(String, String, String)> {
+    if id.contains('.') {
+        let (volume, paper) = id.rsplit_once('.')?;
+        let (collection, volume) = volume.split_once('-')?;
+        Some((collection.to_string(), volume.to_string(), paper.to_string()))
+    } else {
+        let (collection, number) = id.split_once('-')?;
+        if number.len() < 4 || !number.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let (volume, paper) = number.split_at(number.len() - 3);
+        let paper = paper.trim_start_matches('0');
+        Some((collection.to_string(), volume.to_string(), paper.to_string()))
+    }
+}
+
+impl AclVolume {
+    // ingest date, or the first of the month for volumes without one.
+    fn date(&self) -> Result<DateTime<Utc>, ParserError> {
+        let (value, format) = match &self.ingest_date {
+            Some(date) => (date.trim().to_string(), "%Y-%m-%d"),
+            None => (format!("{} {} 1",
+                self.meta.year.trim(),
+                self.meta.month.as_deref().unwrap_or("January").trim()), "%Y %B %d")
+        };
+        NaiveDate::parse_from_str(&value, format)
+            .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+            .map_err(|e| ParserError::Date { value, source: e })
+    }
+}
+
+impl AclPaper {
+    fn to_result(
+        &self,
+        collection: &str,
+        volume: &AclVolume,
+        ingested: DateTime<Utc>,
+        index: usize
+    ) -> ArxivResult {
+        let id = anthology_id(collection, &volume.id, &self.id);
+        let venues: Vec<String> = volume.meta.venues.iter().map(|venue| clean(venue)).collect();
+        ArxivResult {
+            source: Source::AclAnthology,
+            link: format!("https://aclanthology.org/{}", id),
+            id,
+            version: 1,
+            index,
+            title: clean(&self.title),
+            summary: self.summary.as_deref().map(clean).unwrap_or_default(),
+            authors: self.authors.iter()
+                .map(|author| Author::new(
+                    clean(&format!("{} {}", author.first.as_deref().unwrap_or_default(), author.last)),
+                    author.affiliation.iter().map(|a| clean(a)).collect()
+                ))
+                .collect(),
+            published: ingested,
+            updated: ingested,
+            primary_category: venues.first().cloned().unwrap_or_default(),
+            categories: venues,
+            comment: None,
+            journal_ref: Some(clean(&volume.meta.booktitle)).filter(|title| !title.is_empty()),
+            doi: self.doi.as_deref().map(clean),
+            announce_type: None
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AclCollection {
+    #[serde(rename = "@id")]
+    id: String,
+    #[serde(rename = "volume")]
+    volumes: Vec<AclVolume>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AclVolume {
+    #[serde(rename = "@id")]
+    id: String,
+    #[serde(rename = "@ingest-date")]
+    ingest_date: Option<String>,
+    meta: AclMeta,
+    #[serde(rename = "paper")]
+    papers: Vec<AclPaper>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AclMeta {
+    booktitle: String,
+    month: Option<String>,
+    year: String,
+    #[serde(rename = "venue")]
+    venues: Vec<String>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AclPaper {
+    #[serde(rename = "@id")]
+    id: String,
+    title: String,
+    #[serde(rename = "author")]
+    authors: Vec<AclAuthor>,
+    #[serde(rename = "abstract")]
+    summary: Option<String>,
+    doi: Option<String>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AclAuthor {
+    first: Option<String>,
+    last: String,
+    affiliation: Option<String>
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const COLLECTION: &str = r#"<?xml version='1.0' encoding='UTF-8'?>
+    <collection id="2024.acl">
+      <volume id="long" ingest-date="2024-08-05" type="proceedings">
+        <meta>
+          <booktitle>Proceedings of the 62nd Annual Meeting of the <fixed-case>A</fixed-case>ssociation for Computational Linguistics (Volume 1: Long Papers)</booktitle>
+          <editor><first>Lun-Wei</first><last>Ku</last></editor>
+          <publisher>Association for Computational Linguistics</publisher>
+          <address>Bangkok, Thailand</address>
+          <month>August</month>
+          <year>2024</year>
+          <url hash="a1b2c3d4">2024.acl-long</url>
+          <venue>acl</venue>
+        </meta>
+        <frontmatter>
+          <url hash="e5f6a7b8">2024.acl-long.0</url>
+        </frontmatter>
+        <paper id="1">
+          <title>Quantized Side Tuning for <fixed-case>LLM</fixed-case>s</title>
+          <author><first>Jane</first><last>Doe</last><affiliation>MIT</affiliation></author>
+          <author><first>John</first><last>Roe</last></author>
+          <pages>1–17</pages>
+          <abstract>We study <tex-math>x^2</tex-math> and
+            more.</abstract>
+          <url hash="c9d0e1f2">2024.acl-long.1</url>
+          <doi>10.18653/v1/2024.acl-long.1</doi>
+          <bibkey>doe-etal-2024-quantized</bibkey>
+        </paper>
+        <paper id="2">
+          <title>Another Paper</title>
+          <author><first>Max</first><last>Mustermann</last></author>
+          <url hash="a3b4c5d6">2024.acl-long.2</url>
+        </paper>
+      </volume>
+      <volume id="short" type="proceedings">
+        <meta>
+          <booktitle>Proceedings (Volume 2: Short Papers)</booktitle>
+          <month>August</month>
+          <year>2024</year>
+          <venue>acl</venue>
+        </meta>
+      </volume>
+    </collection>"#;
+
+    #[test]
+    fn test_collection_parsing() {
+        let xml = MARKUP.strip(COLLECTION);
+        let collection: AclCollection = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(collection.volumes.len(), 2);
+        let volume = &collection.volumes[0];
+        let ingested = volume.date().unwrap();
+        assert_eq!(ingested, Utc.with_ymd_and_hms(2024, 8, 5, 0, 0, 0).unwrap());
+        assert_eq!(collection.volumes[1].date().unwrap(), Utc.with_ymd_and_hms(2024, 8, 1, 0, 0, 0).unwrap());
+
+        let result = volume.papers[0].to_result(&collection.id, volume, ingested, 0);
+        assert_eq!(result.source, Source::AclAnthology);
+        assert_eq!(result.id, "2024.acl-long.1");
+        assert_eq!(result.title, "Quantized Side Tuning for LLMs");
+        assert_eq!(result.summary, "We study x^2 and more.");
+        assert_eq!(result.authors, vec![
+            Author::new("Jane Doe".to_string(), vec!["MIT".to_string()]),
+            Author::new("John Roe".to_string(), vec![])
+        ]);
+        assert_eq!(result.link, "https://aclanthology.org/2024.acl-long.1");
+        assert_eq!(result.primary_category, "acl");
+        assert_eq!(result.journal_ref.as_deref(), Some(
+            "Proceedings of the 62nd Annual Meeting of the Association for Computational Linguistics (Volume 1: Long Papers)"
+        ));
+        assert_eq!(result.doi.as_deref(), Some("10.18653/v1/2024.acl-long.1"));
+        assert_eq!(volume.papers[1].to_result(&collection.id, volume, ingested, 1).summary, "");
+    }
+
+    #[test]
+    fn test_anthology_ids() {
+        assert_eq!(split_anthology_id("2024.acl-long.1"),
+            Some(("2024.acl".to_string(), "long".to_string(), "1".to_string())));
+        assert_eq!(split_anthology_id("2024.findings-emnlp.12"),
+            Some(("2024.findings".to_string(), "emnlp".to_string(), "12".to_string())));
+        assert_eq!(split_anthology_id("P19-1001"),
+            Some(("P19".to_string(), "1".to_string(), "1".to_string())));
+        assert_eq!(split_anthology_id("acl"), None);
+        assert_eq!(anthology_id("P19", "1", "1"), "P19-1001");
+        assert_eq!(anthology_id("2024.acl", "long", "1"), "2024.acl-long.1");
+    }
+}
@@ -1,89 +1,402 @@
+use async_trait::async_trait;
+use backoff::ExponentialBackoffBuilder;
 use async_openai::{
-    config::OpenAIConfig, 
-    error::OpenAIError, 
+    config::{AzureConfig, Config as OpenAIClientConfig, OpenAIConfig},
+    error::{ApiError, OpenAIError}, 
     types::{
+        ChatChoice,
         ChatCompletionRequestSystemMessageArgs, 
         ChatCompletionRequestUserMessageArgs, 
-        CreateChatCompletionRequestArgs
+        CreateChatCompletionRequest,
+        CreateChatCompletionRequestArgs,
+        FinishReason,
+        ResponseFormat,
+        ResponseFormatJsonSchema
     }, 
     Client as OpenAIClient
 };
+use aws_config::SdkConfig;
 use aws_sdk_bedrockruntime::{
-    error::SdkError, 
-    operation::invoke_model::InvokeModelError, 
+    error::{BuildError, SdkError},
+    operation::converse::{ConverseError, ConverseOutput},
+    types::{
+        ContentBlock,
+        ConversationRole,
+        ConverseOutput as ConverseOutputType,
+        InferenceConfiguration,
+        Message,
+        SpecificToolChoice,
+        StopReason,
+        SystemContentBlock,
+        Tool,
+        ToolChoice,
+        ToolConfiguration,
+        ToolInputSchema,
+        ToolSpecification
+    },
     Client as BedrockClient
 };
-use aws_sdk_s3::{
-    config::http::HttpResponse, 
-    primitives::Blob
-};
-use serde::{Deserialize, Serialize};
-use serde_json::{self, Error as JsonError};
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_smithy_types::{Document, Number};
+use serde_json::{self, Error as JsonError, Value};
 use std::{
     error::Error as StdError,
     fmt, 
-    sync::Arc
+    sync::Arc,
+    time::Duration
 };
-use tokio::task;
+use tokio::{sync::Semaphore, task, time};
 
 use crate::{
-    model::{ArxivResult, ProcessedResult},
-    prompt::PROMPT
+    config::{
+        AgentConfig,
+        AgentProvider,
+        OpenAIApiType,
+        OpenAIEndpoint,
+        OutputFormat,
+        RetryConfig,
+        SummarizerConfig
+    },
+    limiter::RateLimiter,
+    model::{AgentErrorKind, ArxivResult, FailedResult, ProcessedResult, SummarizeOutcome},
+    retry,
+    structured::{self, StructuredSummary}
 };
 
-const OPENAI_MODEL: &str = "gpt-4o-mini";
+/// Common interface over the LLM backends, so that pipelines can hold a
+/// `Box<dyn Summarizer>` and choose the provider at runtime.
+#[async_trait]
+pub trait Summarizer: Send + Sync {
+    async fn summarize(&self, data: Vec<ArxivResult>) -> SummarizeOutcome;
+}
+
+/// Builds the summarizer for `provider`, with the model and prompt of
+/// `agent`. The endpoint is only used by the OpenAI provider, and the AWS
+/// config by providers that talk to AWS services.
+pub fn from_provider(
+    provider: AgentProvider,
+    agent: AgentConfig,
+    config: SummarizerConfig,
+    endpoint: &OpenAIEndpoint,
+    conf: &SdkConfig
+) -> Box<dyn Summarizer> {
+    match (provider, endpoint.api_type) {
+        (AgentProvider::OpenAI, OpenAIApiType::OpenAI) => Box::new(OpenAIAgent::from_config(
+            OpenAIClient::with_config(openai_config(endpoint)),
+            agent,
+            config
+        )),
+        (AgentProvider::OpenAI, OpenAIApiType::Azure) => Box::new(OpenAIAgent::from_config(
+            OpenAIClient::with_config(azure_config(endpoint, &agent.model)),
+            agent,
+            config
+        )),
+        (AgentProvider::Bedrock, _) => Box::new(
+            BedrockAgent::from_config(BedrockClient::new(conf), agent, config)
+        )
+    }
+}
+
+// unset fields keep the async-openai defaults, which read `OPENAI_API_KEY`.
+fn openai_config(endpoint: &OpenAIEndpoint) -> OpenAIConfig {
+    let mut config = OpenAIConfig::new();
+    if let Some(api_base) = &endpoint.api_base {
+        config = config.with_api_base(api_base);
+    }
+    if let Some(api_key) = &endpoint.api_key {
+        config = config.with_api_key(api_key);
+    }
+    if let Some(org_id) = &endpoint.org_id {
+        config = config.with_org_id(org_id);
+    }
+    if let Some(project_id) = &endpoint.project_id {
+        config = config.with_project_id(project_id);
+    }
+    config
+}
+
+fn azure_config(endpoint: &OpenAIEndpoint, model: &str) -> AzureConfig {
+    let mut config = AzureConfig::new()
+        .with_api_base(endpoint.api_base.clone().unwrap_or_default())
+        .with_api_version(endpoint.api_version.clone().unwrap_or_default())
+        .with_deployment_id(endpoint.deployment.as_deref().unwrap_or(model));
+    if let Some(api_key) = &endpoint.api_key {
+        config = config.with_api_key(api_key);
+    }
+    config
+}
+
+// per-paper request, implemented by each backend and driven concurrently
+// by `concurrent_summarize`.
+#[async_trait]
+trait SingleSummarizer: Send + Sync + 'static {
+    fn agent_config(&self) -> &AgentConfig;
+
+    async fn text_summarize(&self, data: &ArxivResult) -> Result<String, AgentError>;
 
-pub struct OpenAIAgent {
-    internal: Arc<OpenAIAgentInternal>
+    // fails with `AgentErrorKind::InvalidOutput` when the response does not
+    // validate against `structured::schema`.
+    async fn structured_summarize(&self, data: &ArxivResult) -> Result<StructuredSummary, AgentError>;
+}
+
+// summary text, with its structured form in structured output mode.
+struct AgentOutput {
+    text: String,
+    structured: Option<StructuredSummary>
+}
+
+// summary in the configured output format. Structured summaries fall back to
+// a text summary when they fail to validate, or when the endpoint rejects the
+// schema request, as many OpenAI-compatible servers do. Every request goes
+// through the limiter.
+async fn single_summarize<T: SingleSummarizer>(
+    internal: &T,
+    limiter: &RateLimiter,
+    data: &ArxivResult
+) -> Result<AgentOutput, AgentError> {
+    let agent = internal.agent_config();
+    if agent.output == OutputFormat::Structured {
+        limiter.acquire(estimate_tokens(agent, OutputFormat::Structured, data)).await;
+        match internal.structured_summarize(data).await {
+            Ok(structured) => return Ok(AgentOutput {
+                text: structured.tldr.clone(),
+                structured: Some(structured)
+            }),
+            Err(e) if matches!(e.kind, AgentErrorKind::InvalidOutput | AgentErrorKind::BadRequest) => {
+                eprintln!("Falling back to a text summary of \"{}\": {}", data.title, e);
+            },
+            Err(e) => return Err(e)
+        }
+    }
+    limiter.acquire(estimate_tokens(agent, OutputFormat::Text, data)).await;
+    let text = internal.text_summarize(data).await?;
+    Ok(AgentOutput { text, structured: None })
+}
+
+// limits shared by every request of an agent: at most `max_in_flight`
+// requests at once, spaced out to stay within the per-minute quotas, and
+// retried according to `retry`.
+struct Throttle {
+    in_flight: Arc<Semaphore>,
+    limiter: Arc<RateLimiter>,
+    retry: Arc<RetryConfig>
+}
+
+impl Throttle {
+    fn from_config(config: &SummarizerConfig) -> Self {
+        Throttle {
+            in_flight: Arc::new(Semaphore::new(config.max_in_flight.max(1))),
+            limiter: Arc::new(RateLimiter::new(
+                config.requests_per_minute,
+                config.tokens_per_minute
+            )),
+            retry: Arc::new(config.retry.clone())
+        }
+    }
+}
+
+// rough token cost of a request (~4 characters per token) plus the
+// completion budget, used for tokens-per-minute accounting. Counts the
+// messages as sent, and the schema in structured mode.
+fn estimate_tokens(agent: &AgentConfig, output: OutputFormat, data: &ArxivResult) -> u32 {
+    let mut prompt = agent.prompt.system.len() + agent.prompt.render(data).len();
+    if output == OutputFormat::Structured {
+        prompt += structured::schema().to_string().len();
+    }
+    (prompt / 4) as u32 + agent.completion_tokens(output)
+}
+
+async fn summarize_with_retry<T: SingleSummarizer>(
+    internal: &T,
+    limiter: &RateLimiter,
+    retry: &RetryConfig,
+    data: &ArxivResult
+) -> Result<AgentOutput, (AgentError, u32)> {
+    let mut attempt: u32 = 1;
+    loop {
+        match single_summarize(internal, limiter, data).await {
+            Err(e) if e.kind.is_retryable() && attempt < retry.max_attempts => {
+                let delay = retry::backoff(retry, attempt, e.retry_after);
+                eprintln!("Retrying \"{}\" in {:?} (attempt {}/{}): {}",
+                    data.title, delay, attempt, retry.max_attempts, e);
+                time::sleep(delay).await;
+                attempt += 1;
+            },
+            result => return result.map_err(|e| (e, attempt))
+        }
+    }
+}
+
+async fn concurrent_summarize<T: SingleSummarizer>(
+    internal: Arc<T>,
+    throttle: &Throttle,
+    data: Vec<ArxivResult>
+) -> SummarizeOutcome {
+    let handles = data.into_iter()
+        .map(|data| { 
+            // kept outside of the task so that a panicking task is still reported.
+            let meta = (data.source, data.id.clone(), data.version, data.title.clone(), data.link.clone());
+            let internal_clone = Arc::clone(&internal);
+            let in_flight = Arc::clone(&throttle.in_flight);
+            let limiter = Arc::clone(&throttle.limiter);
+            let retry = Arc::clone(&throttle.retry);
+            let handle = task::spawn(async move {
+                let summary = match in_flight.acquire_owned().await {
+                    Ok(_permit) => summarize_with_retry(
+                        internal_clone.as_ref(), &limiter, &retry, &data
+                    ).await,
+                    Err(e) => Err((AgentError::new(&format!("Semaphore error: {}", e)), 0))
+                };
+                (data, summary)
+            });
+            (meta, handle)
+        })
+        .collect::<Vec<_>>();
+    
+    let prompt_version = internal.agent_config().prompt.id();
+    let mut outcome = SummarizeOutcome::default();
+    for (meta, handle) in handles {
+        match handle.await {
+            Ok((data, Ok(output))) => outcome.results.push(ProcessedResult {
+                prompt_version: Some(prompt_version.clone()),
+                structured: output.structured,
+                ..ProcessedResult::from_result(data, output.text)
+            }),
+            Ok((data, Err((e, attempts)))) => {
+                eprintln!("Agent error: {}", e);
+                outcome.failures.push(failed_result(data, &e, attempts));
+            },
+            Err(e) => {
+                eprintln!("Join error: {}", e);
+                let (source, id, version, title, link) = meta;
+                outcome.failures.push(FailedResult {
+                    source,
+                    ..FailedResult::new(
+                        id, version, title, link, AgentErrorKind::Other, format!("Join error: {}", e), 0
+                    )
+                });
+            }
+        }
+    }
+    if !outcome.failures.is_empty() {
+        eprintln!("Failed to summarize {} papers:", outcome.failures.len());
+        outcome.failures.iter()
+            .for_each(|failed| eprintln!("  - {} ({}, {} attempts)", 
+                failed.title, failed.error_kind, failed.attempts));
+    }
+    outcome
+}
+
+// `data` as a failure after `attempts` calls ending with `error`.
+fn failed_result(data: ArxivResult, error: &AgentError, attempts: u32) -> FailedResult {
+    FailedResult {
+        source: data.source,
+        ..FailedResult::new(
+            data.id, data.version, data.title, data.link,
+            error.kind, error.message.clone(), attempts
+        )
+    }
+}
+
+/// Chat completions agent, generic over the async-openai `Config` so that the
+/// same code serves OpenAI, OpenAI-compatible servers and Azure OpenAI.
+pub struct OpenAIAgent<C: OpenAIClientConfig = OpenAIConfig> {
+    internal: Arc<OpenAIAgentInternal<C>>,
+    throttle: Throttle
 }
 
 impl OpenAIAgent {
     pub fn new(client: OpenAIClient<OpenAIConfig>) -> Self {
+        Self::from_config(
+            client,
+            AgentConfig::for_provider(AgentProvider::OpenAI),
+            SummarizerConfig::default()
+        )
+    }
+}
+
+impl<C: OpenAIClientConfig + Send + Sync + 'static> OpenAIAgent<C> {
+    pub fn from_config(
+        client: OpenAIClient<C>,
+        agent: AgentConfig,
+        config: SummarizerConfig
+    ) -> Self {
+        // async-openai retries rate limited requests on its own for up to 15
+        // minutes; disable that so `config.retry` is the only policy.
+        let client = client.with_backoff(ExponentialBackoffBuilder::new()
+            .with_max_elapsed_time(Some(Duration::ZERO))
+            .build());
         OpenAIAgent {
-            internal: Arc::new(OpenAIAgentInternal::new(client))
+            internal: Arc::new(OpenAIAgentInternal::new(client, agent)),
+            throttle: Throttle::from_config(&config)
         }
     }
+}
 
-    pub async fn summarize(&self, data: Vec<ArxivResult>) -> Vec<ProcessedResult> {
-        let internal_clone = Arc::clone(&self.internal);
-        internal_clone.concurrent_summarize(data).await
+#[async_trait]
+impl<C: OpenAIClientConfig + Send + Sync + 'static> Summarizer for OpenAIAgent<C> {
+    async fn summarize(&self, data: Vec<ArxivResult>) -> SummarizeOutcome {
+        concurrent_summarize(Arc::clone(&self.internal), &self.throttle, data).await
     }
 }
 
-struct OpenAIAgentInternal {
-    client: OpenAIClient<OpenAIConfig>
+struct OpenAIAgentInternal<C: OpenAIClientConfig> {
+    client: OpenAIClient<C>,
+    agent: AgentConfig
 }
 
-impl OpenAIAgentInternal {
-    pub fn new(client: OpenAIClient<OpenAIConfig>) -> Self {
+impl<C: OpenAIClientConfig> OpenAIAgentInternal<C> {
+    pub fn new(client: OpenAIClient<C>, agent: AgentConfig) -> Self {
         OpenAIAgentInternal {
-            client
+            client,
+            agent
         }
     }
+}
 
-    async fn single_summarize(
-        &self, 
-        data: ArxivResult
-    ) -> Result<ProcessedResult, AgentError> {
-        let request = CreateChatCompletionRequestArgs::default()
-            .model(OPENAI_MODEL)
-            .max_tokens(150_u32)
+impl<C: OpenAIClientConfig> OpenAIAgentInternal<C> {
+    fn request(
+        &self,
+        data: &ArxivResult,
+        output: OutputFormat
+    ) -> CreateChatCompletionRequest {
+        let mut args = CreateChatCompletionRequestArgs::default();
+        if let Some(temperature) = self.agent.temperature {
+            args.temperature(temperature);
+        }
+        if let Some(top_p) = self.agent.top_p {
+            args.top_p(top_p);
+        }
+        if output == OutputFormat::Structured {
+            args.response_format(ResponseFormat::JsonSchema {
+                json_schema: ResponseFormatJsonSchema {
+                    description: Some(structured::SCHEMA_DESCRIPTION.to_string()),
+                    name: structured::SCHEMA_NAME.to_string(),
+                    schema: Some(structured::schema()),
+                    strict: Some(true)
+                }
+            });
+        }
+        args.model(&self.agent.model)
+            .max_tokens(self.agent.completion_tokens(output))
             .messages([
                 ChatCompletionRequestSystemMessageArgs::default()
-                    .content(PROMPT)
+                    .content(self.agent.prompt.system.as_str())
                     .build()
                     .unwrap()
                     .into(),
                 ChatCompletionRequestUserMessageArgs::default()
-                    .content(data.summary.as_str())
+                    .content(self.agent.prompt.render(data))
                     .build()
                     .unwrap()
                     .into(),
             ])
             .build()
-            .unwrap();
+            .unwrap()
+    }
 
-        let summary = self.client
+    async fn complete(&self, request: CreateChatCompletionRequest) -> Result<ChatChoice, AgentError> {
+        let choice = self.client
             .chat()
             .create(request)
             .await
@@ -91,247 +404,277 @@ impl OpenAIAgentInternal {
             .choices
             .into_iter()
             .next()
-            .ok_or(AgentError::new("No completion"))?
-            .message
-            .content
             .ok_or(AgentError::new("No completion"))?;
-        
-        Ok(ProcessedResult::from_result(data, summary))
-    }
-
-    async fn concurrent_summarize(
-        self: Arc<Self>,
-        data: Vec<ArxivResult>
-    ) -> Vec<ProcessedResult> {
-        let handles = data.into_iter()
-            .map(|data| { 
-                let self_clone = Arc::clone(&self);
-                task::spawn(async move {
-                    self_clone.single_summarize(data).await
-                }) 
-            })
-            .collect::<Vec<_>>();
-        
-        let mut results: Vec<ProcessedResult> = Vec::new();
-        for handle in handles {
-            match handle.await {
-                Ok(Ok(result)) => results.push(result),
-                Ok(Err(e)) => eprintln!("Agent error: {}", e),
-                Err(e) => eprintln!("Join error: {}", e)
-            }
+
+        if choice.finish_reason == Some(FinishReason::ContentFilter) {
+            return Err(AgentError::with_kind(
+                AgentErrorKind::ContentFilter, 
+                "Completion blocked by content filter"
+            ));
         }
-        results
+        Ok(choice)
     }
 }
 
-// we hardcode the model id as each model has different input schemas.
-const BEDROCK_MODEL_ID: &str = "us.amazon.nova-lite-v1:0";
+#[async_trait]
+impl<C: OpenAIClientConfig + Send + Sync + 'static> SingleSummarizer for OpenAIAgentInternal<C> {
+    fn agent_config(&self) -> &AgentConfig {
+        &self.agent
+    }
+
+    async fn text_summarize(
+        &self, 
+        data: &ArxivResult
+    ) -> Result<String, AgentError> {
+        self.complete(self.request(data, OutputFormat::Text))
+            .await?
+            .message
+            .content
+            .ok_or(AgentError::new("No completion"))
+    }
+
+    async fn structured_summarize(
+        &self,
+        data: &ArxivResult
+    ) -> Result<StructuredSummary, AgentError> {
+        let choice = self.complete(self.request(data, OutputFormat::Structured)).await?;
+        if let Some(refusal) = choice.message.refusal {
+            return Err(AgentError::with_kind(AgentErrorKind::InvalidOutput, &format!("Refused: {}", refusal)));
+        }
+        // cut off JSON does not parse.
+        if choice.finish_reason == Some(FinishReason::Length) {
+            return Err(AgentError::with_kind(AgentErrorKind::InvalidOutput, "Completion reached max tokens"));
+        }
+        let content = choice.message.content.ok_or(AgentError::new("No completion"))?;
+        StructuredSummary::from_json(&content)
+            .map_err(|e| AgentError::with_kind(AgentErrorKind::InvalidOutput, &e))
+    }
+}
 
 pub struct BedrockAgent {
-    internal: Arc<BedrockAgentInternal>
+    internal: Arc<BedrockAgentInternal>,
+    throttle: Throttle
 }
 
 impl BedrockAgent {
     pub fn new(client: BedrockClient) -> Self {
+        Self::from_config(
+            client,
+            AgentConfig::for_provider(AgentProvider::Bedrock),
+            SummarizerConfig::default()
+        )
+    }
+
+    // requests go through the Converse API, so `agent.model` can be any
+    // Bedrock model id or inference profile that supports it: Claude, Llama,
+    // Mistral and Nova alike.
+    pub fn from_config(client: BedrockClient, agent: AgentConfig, config: SummarizerConfig) -> Self {
         BedrockAgent {
-            internal: Arc::new(BedrockAgentInternal::new(client))
+            internal: Arc::new(BedrockAgentInternal::new(client, agent)),
+            throttle: Throttle::from_config(&config)
         }
     }
+}
 
-    pub async fn summarize(&self, data: Vec<ArxivResult>) -> Vec<ProcessedResult> {
-        let internal_clone = Arc::clone(&self.internal);
-        internal_clone.concurrent_summarize(data).await
+#[async_trait]
+impl Summarizer for BedrockAgent {
+    async fn summarize(&self, data: Vec<ArxivResult>) -> SummarizeOutcome {
+        concurrent_summarize(Arc::clone(&self.internal), &self.throttle, data).await
     }
 }
 
 struct BedrockAgentInternal {
-    client: BedrockClient
+    client: BedrockClient,
+    agent: AgentConfig
 }
 
 impl BedrockAgentInternal {
-    fn new(client: BedrockClient) -> Self {
+    fn new(client: BedrockClient, agent: AgentConfig) -> Self {
         BedrockAgentInternal {
-            client
+            client,
+            agent
         }
     }
-
-    async fn single_summarize(
-        &self, 
-        data: ArxivResult
-    ) -> Result<ProcessedResult, AgentError> {
-        let model_input = ModelInput::default(&data.summary);
-        let input = serde_json::to_string(&model_input).unwrap();
-
-        let raw = self.client.invoke_model()
-            .body(Blob::new(input))
-            .content_type("application/json")
-            .model_id(BEDROCK_MODEL_ID)
-            .send()
-            .await
-            .map_err(AgentError::from)?
-            .body;
-        
-        let response = ModelResponse::from(raw).map_err(AgentError::from)?;
-        response.combine_arxiv(data)
-    }
-
-    async fn concurrent_summarize(
-        self: Arc<Self>,
-        data: Vec<ArxivResult>
-    ) -> Vec<ProcessedResult> {
-        let handles = data.into_iter()
-            .map(|data| { 
-                let self_clone = Arc::clone(&self);
-                task::spawn(async move {
-                    self_clone.single_summarize(data).await
-                }) 
-            })
-            .collect::<Vec<_>>();
-        
-        let mut results: Vec<ProcessedResult> = Vec::new();
-        for handle in handles {
-            match handle.await {
-                Ok(Ok(result)) => results.push(result),
-                Ok(Err(e)) => eprintln!("Agent error: {}", e),
-                Err(e) => eprintln!("Join error: {}", e)
-            }
-        }
-        results
-    }
-}
-
-// request parameters structs.
-
-#[derive(Debug, Serialize)]
-struct ModelInput {
-    system: Vec<BedrockText>,
-    messages: Vec<UserMessage>,
-    #[serde(rename = "inferenceConfig")]
-    inference_config: InferenceConfig
-}
-
-#[derive(Debug, Serialize)]
-struct UserMessage {
-    role: String, // "user"
-    content: Vec<BedrockText>
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct BedrockText {
-    text: String
 }
 
-#[derive(Debug, Serialize)]
-struct InferenceConfig {
-    max_new_tokens: u32, // 150
-    top_p: f32, // 0.9
-    top_k: u32, // 20
-    temperature: f32 // 0.5
-}
+#[async_trait]
+impl SingleSummarizer for BedrockAgentInternal {
+    fn agent_config(&self) -> &AgentConfig {
+        &self.agent
+    }
 
-impl ModelInput {
-    fn new(system: &str, content: &str) -> Self {
-        ModelInput {
-            system: vec![ BedrockText { text: system.to_string() } ],
-            messages: vec![
-                UserMessage {
-                    role: "user".to_string(),
-                    content: vec![ BedrockText { text: content.to_string() } ]
-                }
-            ],
-            inference_config: InferenceConfig::default()
-        }
+    async fn text_summarize(
+        &self, 
+        data: &ArxivResult
+    ) -> Result<String, AgentError> {
+        response_text(&self.converse(data, OutputFormat::Text).await?)
     }
 
-    fn default(content: &str) -> Self {
-        Self::new(PROMPT, content)
+    async fn structured_summarize(
+        &self,
+        data: &ArxivResult
+    ) -> Result<StructuredSummary, AgentError> {
+        response_structured(&self.converse(data, OutputFormat::Structured).await?)
     }
 }
 
-impl InferenceConfig {
-    fn default() -> Self {
-        InferenceConfig {
-            max_new_tokens: 150,
-            top_p: 0.9,
-            top_k: 20,
-            temperature: 0.5
+impl BedrockAgentInternal {
+    async fn converse(&self, data: &ArxivResult, output: OutputFormat) -> Result<ConverseOutput, AgentError> {
+        let inference = InferenceConfiguration::builder()
+            .max_tokens(self.agent.completion_tokens(output) as i32)
+            .set_temperature(self.agent.temperature)
+            .set_top_p(self.agent.top_p)
+            .build();
+        let message = Message::builder()
+            .role(ConversationRole::User)
+            .content(ContentBlock::Text(self.agent.prompt.render(data)))
+            .build()?;
+
+        let mut request = self.client.converse()
+            .model_id(&self.agent.model)
+            .system(SystemContentBlock::Text(self.agent.prompt.system.clone()))
+            .messages(message)
+            .inference_config(inference);
+        if output == OutputFormat::Structured {
+            request = request.tool_config(summary_tool(&self.agent.model)?);
         }
+        let response = request.send().await.map_err(AgentError::from)?;
+        check_stop_reason(&response, output)?;
+        Ok(response)
     }
 }
 
-// response parameter structs.
-
-#[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-struct ModelResponse {
-    output: ModelOutput,
-    #[serde(rename = "stopReason")]
-    stop_reason: String,
-    usage: ModelUsage
+// the structured summary as the input of a tool, the Converse way of
+// requesting JSON output. Models that support it are forced to call the tool;
+// the others choose on their own, and a text answer falls back to a text
+// summary.
+fn summary_tool(model: &str) -> Result<ToolConfiguration, BuildError> {
+    let spec = ToolSpecification::builder()
+        .name(structured::SCHEMA_NAME)
+        .description(structured::SCHEMA_DESCRIPTION)
+        .input_schema(ToolInputSchema::Json(to_document(structured::schema())))
+        .build()?;
+    let mut config = ToolConfiguration::builder().tools(Tool::ToolSpec(spec));
+    if supports_tool_choice(model) {
+        let choice = SpecificToolChoice::builder()
+            .name(structured::SCHEMA_NAME)
+            .build()?;
+        config = config.tool_choice(ToolChoice::Tool(choice));
+    }
+    config.build()
 }
 
-#[derive(Debug, Deserialize)]
-struct ModelOutput {
-    message: ModelMessage
+// Converse rejects a specific tool choice for models other than Claude, Nova
+// and Mistral Large, e.g. Llama. Ids may carry a region prefix such as `us.`.
+fn supports_tool_choice(model: &str) -> bool {
+    ["anthropic.", "amazon.nova", "mistral.mistral-large"].iter()
+        .any(|family| model.contains(family))
 }
 
-#[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-struct ModelMessage {
-    content: Vec<BedrockText>,
-    role: String
+fn check_stop_reason(response: &ConverseOutput, output: OutputFormat) -> Result<(), AgentError> {
+    match response.stop_reason() {
+        StopReason::ContentFiltered | StopReason::GuardrailIntervened => Err(AgentError::with_kind(
+            AgentErrorKind::ContentFilter,
+            "Completion blocked by content filter"
+        )),
+        // cut off tool input does not validate.
+        StopReason::MaxTokens if output == OutputFormat::Structured => Err(AgentError::with_kind(
+            AgentErrorKind::InvalidOutput,
+            "Completion reached max tokens"
+        )),
+        _ => Ok(())
+    }
 }
 
-#[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-struct ModelUsage {
-    #[serde(rename = "inputTokens")]
-    input_tokens: u32,
-    #[serde(rename = "outputTokens")]
-    output_tokens: u32,
-    #[serde(rename = "totalTokens")]
-    total_tokens: u32
+fn response_content(response: &ConverseOutput) -> &[ContentBlock] {
+    match response.output() {
+        Some(ConverseOutputType::Message(message)) => message.content(),
+        _ => &[]
+    }
 }
 
-impl ModelResponse {
-    fn from(raw: Blob) -> Result<Self, JsonError> {
-        let bytes = raw.into_inner();
-        let text = String::from_utf8_lossy(&bytes);
-        serde_json::from_str(&text)
+fn response_text(response: &ConverseOutput) -> Result<String, AgentError> {
+    let summary = response_content(response)
+        .iter()
+        .filter_map(|content| content.as_text().ok())
+        .map(|text| text.trim())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if summary.is_empty() {
+        return Err(AgentError::new("summary is empty"));
     }
+    Ok(summary)
+}
+
+fn response_structured(response: &ConverseOutput) -> Result<StructuredSummary, AgentError> {
+    let input = response_content(response)
+        .iter()
+        .filter_map(|content| content.as_tool_use().ok())
+        .find(|tool_use| tool_use.name() == structured::SCHEMA_NAME)
+        .map(|tool_use| from_document(tool_use.input()))
+        .ok_or(AgentError::with_kind(AgentErrorKind::InvalidOutput, "No tool use in response"))?;
+    StructuredSummary::from_value(input)
+        .map_err(|e| AgentError::with_kind(AgentErrorKind::InvalidOutput, &e))
+}
 
-    fn get_output(self) -> String {
-        self.output.message.content
-            .into_iter().nth(0)
-            .map(|t| { t.text })
-            .unwrap_or_default()
+// tool schemas and inputs are smithy documents rather than JSON values.
+
+fn to_document(value: Value) -> Document {
+    match value {
+        Value::Null => Document::Null,
+        Value::Bool(b) => Document::Bool(b),
+        Value::Number(n) => Document::Number(match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => Number::PosInt(u),
+            (None, Some(i)) => Number::NegInt(i),
+            _ => Number::Float(n.as_f64().unwrap_or_default())
+        }),
+        Value::String(s) => Document::String(s),
+        Value::Array(items) => Document::Array(items.into_iter().map(to_document).collect()),
+        Value::Object(map) => Document::Object(map.into_iter()
+            .map(|(key, value)| (key, to_document(value)))
+            .collect())
     }
+}
 
-    fn combine_arxiv(self, data: ArxivResult) -> Result<ProcessedResult, AgentError> {
-        let summary = self.get_output();
-        if summary.is_empty() {
-            return Err(AgentError::new("summary is empty"));
-        }
-        Ok(ProcessedResult::from_result(data, summary))
+fn from_document(document: &Document) -> Value {
+    match document {
+        Document::Null => Value::Null,
+        Document::Bool(b) => Value::Bool(*b),
+        Document::Number(Number::PosInt(u)) => Value::from(*u),
+        Document::Number(Number::NegInt(i)) => Value::from(*i),
+        Document::Number(Number::Float(f)) => Value::from(*f),
+        Document::String(s) => Value::String(s.clone()),
+        Document::Array(items) => Value::Array(items.iter().map(from_document).collect()),
+        Document::Object(map) => Value::Object(map.iter()
+            .map(|(key, value)| (key.clone(), from_document(value)))
+            .collect())
     }
 }
 
 #[derive(Debug)]
 pub struct AgentError {
-    pub message: String
+    pub kind: AgentErrorKind,
+    pub message: String,
+    pub retry_after: Option<Duration>
 }
 
 impl AgentError {
     pub fn new(message: &str) -> Self {
+        Self::with_kind(AgentErrorKind::Other, message)
+    }
+
+    pub fn with_kind(kind: AgentErrorKind, message: &str) -> Self {
         Self {
-            message: message.to_string()
+            kind,
+            message: message.to_string(),
+            retry_after: None
         }
     }
 }
 
 impl fmt::Display for AgentError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.message)
+        write!(f, "[{}] {}", self.kind, self.message)
     }
 }
 
@@ -343,14 +686,164 @@ impl From<JsonError> for AgentError {
     }
 }
 
-impl From<SdkError<InvokeModelError, HttpResponse>> for AgentError {
-    fn from(err: SdkError<InvokeModelError, HttpResponse>) -> Self {
-        AgentError::new(&format!("AWS SDK error: {}. Details: {:?}", err, err.raw_response()))
+impl From<BuildError> for AgentError {
+    fn from(err: BuildError) -> Self {
+        AgentError::with_kind(AgentErrorKind::BadRequest, &format!("Invalid request: {}", err))
+    }
+}
+
+impl From<SdkError<ConverseError, HttpResponse>> for AgentError {
+    fn from(err: SdkError<ConverseError, HttpResponse>) -> Self {
+        let kind = match &err {
+            SdkError::TimeoutError(_) => AgentErrorKind::Timeout,
+            SdkError::DispatchFailure(e) if e.is_timeout() || e.is_io() => AgentErrorKind::Timeout,
+            SdkError::ResponseError(_) => AgentErrorKind::Server,
+            SdkError::ServiceError(ctx) => match ctx.err() {
+                ConverseError::ThrottlingException(_) => AgentErrorKind::RateLimit,
+                ConverseError::ModelTimeoutException(_) => AgentErrorKind::Timeout,
+                ConverseError::InternalServerException(_)
+                | ConverseError::ServiceUnavailableException(_)
+                | ConverseError::ModelNotReadyException(_)
+                | ConverseError::ModelErrorException(_) => AgentErrorKind::Server,
+                ConverseError::ValidationException(_)
+                | ConverseError::AccessDeniedException(_)
+                | ConverseError::ResourceNotFoundException(_) => AgentErrorKind::BadRequest,
+                _ => AgentErrorKind::Other
+            },
+            _ => AgentErrorKind::Other
+        };
+        let retry_after = err.raw_response()
+            .and_then(|response| response.headers().get("retry-after"))
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+
+        let mut error = AgentError::with_kind(
+            kind, 
+            &format!("AWS SDK error: {}. Details: {:?}", err, err.raw_response())
+        );
+        error.retry_after = retry_after;
+        error
     }
 }
 
 impl From<OpenAIError> for AgentError {
     fn from(err: OpenAIError) -> Self {
-        AgentError::new(&format!("Open AI Error: {}", err))
+        let kind = match &err {
+            OpenAIError::ApiError(e) => classify_openai_error(e),
+            // connection failures are treated like timeouts: the request
+            // never completed and is safe to send again.
+            OpenAIError::Reqwest(e) if e.is_timeout() || e.is_connect() => AgentErrorKind::Timeout,
+            OpenAIError::Reqwest(e) => match e.status() {
+                Some(status) if status.as_u16() == 429 => AgentErrorKind::RateLimit,
+                Some(status) if status.is_server_error() => AgentErrorKind::Server,
+                Some(status) if status.is_client_error() => AgentErrorKind::BadRequest,
+                _ => AgentErrorKind::Other
+            },
+            OpenAIError::InvalidArgument(_) => AgentErrorKind::BadRequest,
+            _ => AgentErrorKind::Other
+        };
+        AgentError::with_kind(kind, &format!("Open AI Error: {}", err))
+    }
+}
+
+// async-openai does not expose the HTTP status of API errors, so they are
+// classified by the `type` and `code` fields of the error body.
+fn classify_openai_error(err: &ApiError) -> AgentErrorKind {
+    let error_type = err.r#type.as_deref().unwrap_or_default();
+    let code = err.code.as_deref().unwrap_or_default();
+    match (error_type, code) {
+        (_, "content_filter") | (_, "content_policy_violation") => AgentErrorKind::ContentFilter,
+        (_, "rate_limit_exceeded") | ("requests", _) | ("tokens", _) => AgentErrorKind::RateLimit,
+        ("server_error", _) | (_, "server_error") => AgentErrorKind::Server,
+        ("invalid_request_error", _) => AgentErrorKind::BadRequest,
+        _ => AgentErrorKind::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_bedrockruntime::types::ToolUseBlock;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{config::AgentProvider, prompt::PromptTemplate};
+
+    fn response(content: ContentBlock, stop_reason: StopReason) -> ConverseOutput {
+        let message = Message::builder()
+            .role(ConversationRole::Assistant)
+            .content(content)
+            .build()
+            .unwrap();
+        ConverseOutput::builder()
+            .output(ConverseOutputType::Message(message))
+            .stop_reason(stop_reason)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_document_roundtrip() {
+        let value = json!({
+            "name": "x", "count": 3, "offset": -2, "ratio": 0.5, "tags": ["a", null, true]
+        });
+        assert_eq!(from_document(&to_document(value.clone())), value);
+        assert!(to_document(structured::schema()).as_object().unwrap().contains_key("properties"));
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        let agent = AgentConfig {
+            prompt: PromptTemplate::builtin("summary-v2").unwrap(),
+            ..AgentConfig::for_provider(AgentProvider::OpenAI)
+        };
+        let data = ArxivResult {
+            title: "t".repeat(400),
+            summary: "a".repeat(4000),
+            ..Default::default()
+        };
+        let rendered = agent.prompt.system.len() + agent.prompt.render(&data).len();
+        let text = estimate_tokens(&agent, OutputFormat::Text, &data);
+        assert_eq!(text, (rendered / 4) as u32 + agent.max_tokens);
+        assert!(text > (4400 / 4) as u32);
+        assert!(estimate_tokens(&agent, OutputFormat::Structured, &data) > text);
+    }
+
+    #[test]
+    fn test_summary_tool() {
+        let tool = summary_tool("us.anthropic.claude-3-5-haiku-20241022-v1:0").unwrap();
+        assert!(tool.tool_choice().is_some_and(ToolChoice::is_tool));
+        let tool = summary_tool("us.meta.llama3-3-70b-instruct-v1:0").unwrap();
+        assert!(tool.tool_choice().is_none());
+        assert_eq!(tool.tools().len(), 1);
+    }
+
+    #[test]
+    fn test_converse_response() {
+        let input = json!({
+            "tldr": "Sparse attention in linear time.",
+            "contributions": ["A new kernel"],
+            "method": "Hashes queries into buckets.",
+            "results": "2x faster.",
+            "limitations": "",
+            "tags": ["attention"]
+        });
+        let tool_use = ToolUseBlock::builder()
+            .tool_use_id("tooluse_1")
+            .name(structured::SCHEMA_NAME)
+            .input(to_document(input))
+            .build()
+            .unwrap();
+        let structured = response(ContentBlock::ToolUse(tool_use), StopReason::ToolUse);
+        assert_eq!(response_structured(&structured).unwrap().tags, vec!["attention"]);
+
+        let text = response(ContentBlock::Text(" A summary. ".to_string()), StopReason::EndTurn);
+        assert_eq!(response_text(&text).unwrap(), "A summary.");
+        assert_eq!(response_structured(&text).unwrap_err().kind, AgentErrorKind::InvalidOutput);
+
+        let filtered = response(ContentBlock::Text(String::new()), StopReason::ContentFiltered);
+        assert_eq!(check_stop_reason(&filtered, OutputFormat::Text).unwrap_err().kind, AgentErrorKind::ContentFilter);
+        let cut_off = response(ContentBlock::Text(String::new()), StopReason::MaxTokens);
+        assert!(check_stop_reason(&cut_off, OutputFormat::Text).is_ok());
+        assert_eq!(check_stop_reason(&cut_off, OutputFormat::Structured).unwrap_err().kind, AgentErrorKind::InvalidOutput);
     }
 }
@@ -1,13 +1,15 @@
-use async_openai::Client as OpenAIClient;
 use aws_config::Region;
 use aws_sdk_s3::Client as S3Client;
+use chrono::NaiveDate;
 use lambda_runtime::{service_fn, LambdaEvent, Error as LambdaError};
 use serde_json::Value;
 
 use paperscraper::{
-    agent::OpenAIAgent,
-    config::{ArxivConfig, NameConfig}, 
-    parser::ArxivParser, 
+    agent,
+    config::{AgentConfig, AgentProvider, ArxivConfig, DedupConfig, NameConfig, OpenAIEndpoint, SummarizerConfig}, 
+    model::DateRange,
+    pipeline::Pipeline,
+    source,
     storage::S3Storage
 };
 
@@ -19,37 +21,43 @@ async fn main() -> Result<(), LambdaError> {
 }
 
 // when testing, lambda functions cannot accept LambdaEvent<()>
-async fn func(_event: LambdaEvent<Value>) -> Result<(), LambdaError> {
+// an event of the form {"from": "2025-01-01", "to": "2025-01-08"} backfills
+// the days in [from, to), any other event processes the default day.
+async fn func(event: LambdaEvent<Value>) -> Result<(), LambdaError> {
     let region = get_env_string("REGION");
     let bucket = get_env_string("BUCKET");
 
-    let parser_config = ArxivConfig::default();
-    let name_config = NameConfig::default(&bucket);
-    let parser = ArxivParser::from_config(parser_config);
-    let data = parser.get_arxiv_results(None).await;
-
-    if data.is_empty() {
-        println!("No results. Exiting...");
-        return Ok(());
-    }
-    
     let conf = aws_config::from_env()
         .region(Region::new(region))
         .load()
         .await;
     let s3_client = S3Client::new(&conf);
     let s3_storage = S3Storage::default(s3_client);
-    let openai_client = OpenAIClient::new();
-    let agent = OpenAIAgent::new(openai_client);
-    let data = agent.summarize(data).await;
-    let key = name_config.jsonl_path();
-    let _ = s3_storage.upload_arxiv_as_jsonl(
-        &bucket, 
-        &key, 
-        &data).await?;
+    let provider = AgentProvider::from_env();
+    let agent = agent::from_provider(
+        provider,
+        AgentConfig::from_env(provider),
+        SummarizerConfig::from_env(), 
+        &OpenAIEndpoint::from_env(),
+        &conf);
+    let sources = source::from_env(&ArxivConfig::default());
+    let pipeline = Pipeline::new(sources, agent, s3_storage, NameConfig::default(&bucket))?
+        .with_dedup(DedupConfig::from_env());
+
+    match (get_event_date(&event.payload, "from")?, get_event_date(&event.payload, "to")?) {
+        (Some(from), Some(to)) => pipeline.backfill(&DateRange::days(from, to)).await?,
+        _ => pipeline.run_day(&pipeline.default_range()).await?
+    }
     Ok(())
 }
 
+fn get_event_date(payload: &Value, key: &str) -> Result<Option<NaiveDate>, LambdaError> {
+    match payload.get(key).and_then(Value::as_str) {
+        Some(date) => Ok(Some(NaiveDate::parse_from_str(date, "%Y-%m-%d")?)),
+        None => Ok(None)
+    }
+}
+
 fn get_env_string(key: &str) -> String {
-    std::env::var(key).expect(&format!("{} not found in env", key))
+    std::env::var(key).unwrap_or_else(|_| panic!("{} not found in env", key))
 }
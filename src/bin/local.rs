@@ -1,32 +1,24 @@
-use std::{io, env};
-use dotenvy;
-use async_openai::Client as OpenAIClient;
+use std::{env, error::Error as StdError};
 use aws_config::Region;
-use aws_sdk_bedrockruntime::Client as BedrockClient;
 use aws_sdk_s3::Client as S3Client;
+use chrono::NaiveDate;
 use paperscraper::{
-    agent::{BedrockAgent, OpenAIAgent}, 
-    config::ArxivConfig, 
-    model::ArxivResult, 
-    parser::ArxivParser, 
+    agent, 
+    config::{AgentConfig, AgentProvider, ArxivConfig, DedupConfig, NameConfig, OpenAIEndpoint, SummarizerConfig}, 
+    model::DateRange,
+    pipeline::Pipeline,
+    source,
     storage::S3Storage
 };
 
+// usage:
+//   local                                  processes the default day
+//   local backfill 2025-01-01 2025-01-08   processes each day in [from, to)
+//   local ids 2501.01234 2501.05678v2      processes the given papers
 #[tokio::main]
-async fn main() -> io::Result<()> {
-    // get arxiv data
+async fn main() -> Result<(), Box<dyn StdError>> {
     let config = ArxivConfig::from_env();
-    let parser = ArxivParser::from_config(config);
-    let results = parser.get_arxiv_results(None).await;
-    println!("# results: {}", results.len());
-    if results.len() > 0 {
-        // write arxiv data to AWS S3
-        process_results(results).await;
-    }
-    Ok(())
-}
 
-async fn process_results(data: Vec<ArxivResult>) {
     dotenvy::from_filename("local.env").unwrap();
     let region = get_env_string("REGION");
     let bucket = get_env_string("BUCKET");
@@ -39,23 +31,50 @@ async fn process_results(data: Vec<ArxivResult>) {
     let client = S3Client::new(&conf);
     let s3_storage = S3Storage::new(client, false);
 
-    // Bedrock Agent
-    // let client = BedrockClient::new(&conf);
-    // let agent = BedrockAgent::new(client);
-
-    // OpenAI API Agent
-    let client = OpenAIClient::new();
-    let agent = OpenAIAgent::new(client);
-
-    let data = agent.summarize(data).await;
-    let key = "local/processed.jsonl";
-    let result = s3_storage.upload_arxiv_as_jsonl(
-        &bucket, 
-        key, 
-        &data).await.unwrap();
-    println!("{:?}", result);
+    // `AGENT` selects the provider (openai, bedrock), `AGENT_MODEL` and the
+    // other `AGENT_*` keys its model and prompt, `OPENAI_API_BASE` an
+    // OpenAI-compatible server such as a local Ollama
+    let provider = AgentProvider::from_env();
+    let agent = agent::from_provider(
+        provider,
+        AgentConfig::from_env(provider),
+        SummarizerConfig::from_env(), 
+        &OpenAIEndpoint::from_env(),
+        &conf);
+
+    let names = NameConfig::default(&bucket).with_prefix("local");
+    // `SOURCE` lists the sources (api, oai, rss, biorxiv, medrxiv, acl,
+    // openreview, pubmed)
+    let sources = source::from_env(&config);
+    // `DEDUP` (off, skip, update) skips papers summarized by earlier runs
+    let pipeline = Pipeline::new(sources, agent, s3_storage, names)?
+        .with_dedup(DedupConfig::from_env());
+
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("backfill") => {
+            let from = parse_date_arg(args.get(2))?;
+            let to = parse_date_arg(args.get(3))?;
+            pipeline.backfill(&DateRange::days(from, to)).await?;
+        },
+        Some("ids") => {
+            let ids: Vec<&str> = args[2..].iter().map(String::as_str).collect();
+            if ids.is_empty() {
+                return Err("ids expects at least one arXiv id".into());
+            }
+            pipeline.run_ids(&ids).await?;
+        },
+        Some(command) => return Err(format!("unknown command: {}", command).into()),
+        None => pipeline.run_day(&pipeline.default_range()).await?
+    }
+    Ok(())
+}
+
+fn parse_date_arg(arg: Option<&String>) -> Result<NaiveDate, Box<dyn StdError>> {
+    let arg = arg.ok_or("backfill expects <from> <to> dates (YYYY-MM-DD)")?;
+    Ok(NaiveDate::parse_from_str(arg, "%Y-%m-%d")?)
 }
 
 fn get_env_string(key: &str) -> String {
-    env::var(key).expect(&format!("{} not found in env", key))
+    env::var(key).unwrap_or_else(|_| panic!("{} not found in env", key))
 }
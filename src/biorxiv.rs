@@ -0,0 +1,316 @@
+use std::{
+    collections::HashMap,
+    time::Duration
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    config::BiorxivConfig,
+    http::HttpClient,
+    model::{split_authors, ArxivResult, Author, DateRange, Source},
+    parser::ParserError,
+    source::{clean, previous_day, require_text, skip_malformed, PaperSource}
+};
+
+// bioRxiv and medRxiv `details` API. See https://api.biorxiv.org
+//
+// Every posted version is a record of its own, dated with the day it was
+// posted, so a day holds new preprints as well as revisions.
+
+const BIORXIV_API_URL: &str = "https://api.biorxiv.org/details";
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub struct BiorxivApi {
+    source: Source,
+    config: BiorxivConfig,
+    client: HttpClient
+}
+
+impl BiorxivApi {
+    fn new(source: Source, config: BiorxivConfig) -> Self {
+        BiorxivApi {
+            source,
+            client: HttpClient::new(REQUEST_INTERVAL, config.retry.clone()),
+            config
+        }
+    }
+
+    pub fn biorxiv(config: BiorxivConfig) -> Self {
+        Self::new(Source::Biorxiv, config)
+    }
+
+    pub fn medrxiv(config: BiorxivConfig) -> Self {
+        Self::new(Source::Medrxiv, config)
+    }
+
+    fn server(&self) -> &'static str {
+        match self.source {
+            Source::Medrxiv => "medrxiv",
+            _ => "biorxiv"
+        }
+    }
+
+    fn details_url(&self, range: &DateRange, cursor: usize, category: Option<&str>) -> String {
+        // both ends are inclusive days.
+        let until = (range.to - chrono::Duration::seconds(1)).date_naive();
+        let url = format!("{}/{}/{}/{}/{}/json",
+            BIORXIV_API_URL,
+            self.server(),
+            range.from.format("%Y-%m-%d"),
+            until.format("%Y-%m-%d"),
+            cursor);
+        match category {
+            Some(category) => Url::parse_with_params(&url, &[("category", category)])
+                .map(|url| url.to_string())
+                .unwrap_or(url),
+            None => url
+        }
+    }
+
+    async fn fetch_category(
+        &self,
+        range: &DateRange,
+        category: Option<&str>
+    ) -> Result<Vec<ArxivResult>, ParserError> {
+        let mut results: Vec<ArxivResult> = Vec::new();
+        let mut cursor: usize = 0;
+        loop {
+            let url = self.details_url(range, cursor, category);
+            println!("using {} url: {}", self.source, url);
+            let response: DetailsResponse = serde_json::from_str(&self.client.get(&url).await?)?;
+            let total = response.messages.first()
+                .and_then(|message| count(&message.total))
+                .unwrap_or(0);
+            if response.collection.is_empty() {
+                break;
+            }
+            cursor += response.collection.len();
+            for record in response.collection {
+                if let Some(result) = skip_malformed(record.into_result(self.source, results.len()))? {
+                    results.push(result);
+                }
+            }
+            if cursor >= total {
+                break;
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[async_trait]
+impl PaperSource for BiorxivApi {
+    fn source(&self) -> Source {
+        self.source
+    }
+
+    fn default_range(&self) -> DateRange {
+        previous_day()
+    }
+
+    async fn fetch(&self, range: &DateRange) -> Result<Vec<ArxivResult>, ParserError> {
+        let mut results: Vec<ArxivResult> = Vec::new();
+        if self.config.categories.is_empty() {
+            results = self.fetch_category(range, None).await?;
+        } else {
+            for category in &self.config.categories {
+                results.extend(self.fetch_category(range, Some(category)).await?);
+            }
+        }
+        Ok(latest_versions(results))
+    }
+
+    /// Preprints by DOI, e.g. `10.1101/2024.12.30.630000`.
+    async fn fetch_by_ids(&self, ids: &[&str]) -> Result<Vec<ArxivResult>, ParserError> {
+        let mut results: Vec<ArxivResult> = Vec::new();
+        for id in ids {
+            let url = format!("{}/{}/{}/na/json", BIORXIV_API_URL, self.server(), id.trim());
+            let response: DetailsResponse = serde_json::from_str(&self.client.get(&url).await?)?;
+            if response.collection.is_empty() {
+                eprintln!("Warning: {} not found", id);
+            }
+            for record in response.collection {
+                if let Some(result) = skip_malformed(record.into_result(self.source, results.len()))? {
+                    results.push(result);
+                }
+            }
+        }
+        Ok(latest_versions(results))
+    }
+}
+
+// counts are sent as numbers or strings.
+fn count(value: &Value) -> Option<usize> {
+    match value {
+        Value::Number(number) => number.as_u64().map(|n| n as usize),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None
+    }
+}
+
+// keeps the latest version of every preprint, in order of first appearance.
+fn latest_versions(results: Vec<ArxivResult>) -> Vec<ArxivResult> {
+    let mut latest: Vec<ArxivResult> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for result in results {
+        match positions.get(&result.id) {
+            Some(&position) if latest[position].version >= result.version => (),
+            Some(&position) => latest[position] = result,
+            None => {
+                positions.insert(result.id.clone(), latest.len());
+                latest.push(result);
+            }
+        }
+    }
+    latest.iter_mut()
+        .enumerate()
+        .for_each(|(index, result)| result.index = index);
+    latest
+}
+
+// `Doe, J.; Roe, J. A.` -> `J. Doe`, `J. A. Roe`
+fn parse_authors(authors: &str) -> Vec<Author> {
+    authors.split(';')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| match name.split_once(',') {
+            Some((last, first)) => Author::new(clean(&format!("{} {}", first, last)), vec![]),
+            None => split_authors(name).into_iter().next()
+                .unwrap_or_else(|| Author::new(clean(name), vec![]))
+        })
+        .collect()
+}
+
+impl DetailsRecord {
+    fn into_result(self, source: Source, index: usize) -> Result<ArxivResult, ParserError> {
+        let date = NaiveDate::parse_from_str(self.date.trim(), "%Y-%m-%d")
+            .map_err(|e| ParserError::Date { value: self.date.clone(), source: e })?;
+        let posted: DateTime<Utc> = date.and_time(NaiveTime::MIN).and_utc();
+        let version = self.version.trim().parse().unwrap_or(1);
+        let server = match source {
+            Source::Medrxiv => "medrxiv",
+            _ => "biorxiv"
+        };
+        let doi = self.doi.trim().to_string();
+        // DOI of the journal version, `NA` until published. It replaces the
+        // preprint DOI, which remains the id.
+        let published = Some(self.published.trim().to_string())
+            .filter(|published| !published.is_empty() && published != "NA");
+        let category = clean(&self.category);
+        Ok(ArxivResult {
+            source,
+            link: format!("https://www.{}.org/content/{}v{}", server, doi, version),
+            id: doi.clone(),
+            version,
+            index,
+            title: clean(&self.title),
+            summary: clean(&self.summary),
+            authors: parse_authors(&self.authors),
+            published: posted,
+            updated: posted,
+            primary_category: category.clone(),
+            categories: vec![category],
+            comment: None,
+            journal_ref: None,
+            doi: Some(published.unwrap_or(doi)),
+            announce_type: None
+        }).and_then(require_text)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DetailsResponse {
+    messages: Vec<DetailsMessage>,
+    collection: Vec<DetailsRecord>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DetailsMessage {
+    total: Value
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DetailsRecord {
+    doi: String,
+    title: String,
+    authors: String,
+    date: String,
+    version: String,
+    category: String,
+    #[serde(rename = "abstract")]
+    summary: String,
+    published: String
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const DETAILS: &str = r#"{
+        "messages": [{"status": "ok", "interval": "2025-01-02:2025-01-02", "cursor": 0, "count": 3, "total": "3"}],
+        "collection": [
+            {"doi": "10.1101/2024.12.30.630000", "title": "A   Preprint", "authors": "Doe, J.; Roe, J. A.",
+             "author_corresponding": "Jane Doe", "author_corresponding_institution": "MIT",
+             "date": "2025-01-02", "version": "1", "type": "new results", "license": "cc_by",
+             "category": "neuroscience", "abstract": " An abstract. ", "published": "NA", "server": "bioRxiv"},
+            {"doi": "10.1101/2024.12.30.630000", "title": "A Preprint", "authors": "Doe, J.; Roe, J. A.",
+             "date": "2025-01-02", "version": "2", "category": "neuroscience", "abstract": "A revision.",
+             "published": "10.1234/journal.5678", "server": "bioRxiv"},
+            {"doi": "10.1101/2024.12.31.630001", "title": "Another Preprint", "authors": "Mustermann, M.",
+             "date": "2025-01-02", "version": "1", "category": "cell biology", "abstract": "Another abstract.",
+             "published": "NA", "server": "bioRxiv"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_details_parsing() {
+        let response: DetailsResponse = serde_json::from_str(DETAILS).unwrap();
+        assert_eq!(count(&response.messages[0].total), Some(3));
+        let results: Vec<ArxivResult> = response.collection.into_iter()
+            .enumerate()
+            .map(|(index, record)| record.into_result(Source::Biorxiv, index).unwrap())
+            .collect();
+        let results = latest_versions(results);
+        assert_eq!(results.len(), 2);
+
+        let first = &results[0];
+        assert_eq!(first.source, Source::Biorxiv);
+        assert_eq!(first.id, "10.1101/2024.12.30.630000");
+        assert_eq!(first.version, 2);
+        assert_eq!(first.summary, "A revision.");
+        assert_eq!(first.authors, vec![
+            Author::new("J. Doe".to_string(), vec![]),
+            Author::new("J. A. Roe".to_string(), vec![])
+        ]);
+        assert_eq!(first.published, Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap());
+        assert_eq!(first.link, "https://www.biorxiv.org/content/10.1101/2024.12.30.630000v2");
+        assert_eq!(first.journal_ref, None);
+        assert_eq!(first.doi.as_deref(), Some("10.1234/journal.5678"));
+
+        let second = &results[1];
+        assert_eq!(second.index, 1);
+        assert_eq!(second.primary_category, "cell biology");
+        assert_eq!(second.doi.as_deref(), Some("10.1101/2024.12.31.630001"));
+    }
+
+    #[test]
+    fn test_details_url() {
+        let api = BiorxivApi::medrxiv(BiorxivConfig::new(vec![]));
+        let range = DateRange::days(
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 1, 3).unwrap()
+        );
+        assert_eq!(api.details_url(&range, 100, Some("cell_biology")),
+            "https://api.biorxiv.org/details/medrxiv/2025-01-01/2025-01-02/100/json?category=cell_biology");
+    }
+}
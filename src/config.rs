@@ -1,39 +1,58 @@
 use dotenvy;
-use std::{env, process};
-use chrono::Utc;
+use std::{collections::HashMap, env, fs, process, time::Duration};
+use chrono::{NaiveDate, Utc};
+
+use crate::{prompt::PromptTemplate, query::SearchQuery};
 
 const PROCESSED_FOLDER_PREFIX: &str = "summaries";
 
-#[derive(Debug)]
+const ARXIV_API_URL: &str = "https://export.arxiv.org/api/query/";
+
+const ARXIV_OAI_URL: &str = "https://oaipmh.arxiv.org/oai";
+
+// arXiv asks API clients to wait 3 seconds between consecutive calls.
+const ARXIV_REQUEST_INTERVAL: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone)]
 pub struct ArxivConfig {
     pub num_entries: i32,
     pub num_pages: i32,
     pub date_offset: i32,
     pub categories: Vec<String>,
+    // additional terms ANDed with the categories, e.g. keyword filters.
+    pub query: Option<SearchQuery>,
+    // search API endpoint, e.g. a mirror or a proxy.
+    pub api_url: String,
+    // OAI-PMH endpoint, same as `api_url` for harvesting.
+    pub oai_url: String,
+    pub request_interval: Duration,
+    pub retry: RetryConfig
 }
 
-#[allow(dead_code)]
-impl ArxivConfig {
-    pub fn default() -> Self {
-        ArxivConfig {
-            num_entries: 50,
-            num_pages: 10,
-            date_offset: 1,
-            categories: vec![
-                String::from("cs.CL"),
-                String::from("cs.AI"),
-                String::from("cs.LG"),
-                String::from("cs.MA")
-            ]
-        }
+impl Default for ArxivConfig {
+    fn default() -> Self {
+        Self::new(50, 10, 1, vec![
+            String::from("cs.CL"),
+            String::from("cs.AI"),
+            String::from("cs.LG"),
+            String::from("cs.MA")
+        ])
     }
+}
 
+#[allow(dead_code)]
+impl ArxivConfig {
     pub fn new(num_entries: i32, num_pages: i32, date_offset: i32, categories: Vec<String>) -> Self {
         ArxivConfig {
             num_entries,
             num_pages,
             date_offset,
-            categories
+            categories,
+            query: None,
+            api_url: ARXIV_API_URL.to_string(),
+            oai_url: ARXIV_OAI_URL.to_string(),
+            request_interval: ARXIV_REQUEST_INTERVAL,
+            retry: RetryConfig::new(4, ARXIV_REQUEST_INTERVAL, Duration::from_secs(60))
         }
     }
 
@@ -47,13 +66,30 @@ impl ArxivConfig {
             .split_whitespace()
             .map(String::from)
             .collect();
-        Self::new(num_entries, num_pages, date_offset, categories)
+
+        let mut config = Self::new(num_entries, num_pages, date_offset, categories);
+        // `QUERY` uses the arXiv search syntax, e.g. `abs:"retrieval augmented" ANDNOT ti:survey`
+        config.query = env::var("QUERY").ok()
+            .filter(|query| !query.trim().is_empty())
+            .map(|query| SearchQuery::raw(&query));
+        if let Some(url) = env::var("ARXIV_API_URL").ok().filter(|url| !url.trim().is_empty()) {
+            config.api_url = url.trim().to_string();
+        }
+        if let Some(url) = env::var("ARXIV_OAI_URL").ok().filter(|url| !url.trim().is_empty()) {
+            config.oai_url = url.trim().to_string();
+        }
+        // arXiv's limit is a floor: the interval can only be made longer.
+        if let Some(ms) = get_optional_positive_u32_from_env("ARXIV_REQUEST_INTERVAL_MS") {
+            config.request_interval = Duration::from_millis(ms as u64).max(ARXIV_REQUEST_INTERVAL);
+        }
+        config.retry = RetryConfig::from_env("ARXIV", config.retry);
+        config
     }
 }
 
 fn get_positive_i32_from_env(key: &str) -> i32 {
     let var: i32 = env::var(key)
-        .expect(&format!("{} not found in env", key))
+        .unwrap_or_else(|_| panic!("{} not found in env", key))
         .parse()
         .unwrap_or_else(|_| {
             eprintln!("Failed to parse NUM_ENTRIES as i32");
@@ -63,9 +99,579 @@ fn get_positive_i32_from_env(key: &str) -> i32 {
     var
 }
 
+// where papers are fetched from. The arXiv search API, OAI-PMH bulk
+// harvesting and the daily announcement feeds are alternative arXiv clients;
+// the others are further preprint servers and venues.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SourceKind {
+    Api,
+    Oai,
+    Rss,
+    Biorxiv,
+    Medrxiv,
+    Acl,
+    OpenReview,
+    Pubmed
+}
+
+impl SourceKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "api" => Some(SourceKind::Api),
+            "oai" => Some(SourceKind::Oai),
+            "rss" => Some(SourceKind::Rss),
+            "biorxiv" => Some(SourceKind::Biorxiv),
+            "medrxiv" => Some(SourceKind::Medrxiv),
+            "acl" => Some(SourceKind::Acl),
+            "openreview" => Some(SourceKind::OpenReview),
+            "pubmed" => Some(SourceKind::Pubmed),
+            _ => None
+        }
+    }
+
+    // `SOURCE` is optional and defaults to the arXiv search API. Several
+    // sources are combined into one digest, e.g. `SOURCE="api biorxiv"`.
+    pub fn from_env() -> Vec<Self> {
+        match env::var("SOURCE") {
+            Ok(names) => names.split_whitespace()
+                .map(|name| Self::from_name(name)
+                    .unwrap_or_else(|| panic!("unknown SOURCE: {}", name)))
+                .collect(),
+            Err(_) => vec![SourceKind::Api]
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AgentProvider {
+    OpenAI,
+    Bedrock
+}
+
+impl AgentProvider {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "openai" => Some(AgentProvider::OpenAI),
+            "bedrock" => Some(AgentProvider::Bedrock),
+            _ => None
+        }
+    }
+
+    // `AGENT` is optional and defaults to OpenAI.
+    pub fn from_env() -> Self {
+        match env::var("AGENT") {
+            Ok(name) => Self::from_name(&name)
+                .unwrap_or_else(|| panic!("unknown AGENT: {}", name)),
+            Err(_) => AgentProvider::OpenAI
+        }
+    }
+}
+
+// completion budget for a three sentence summary.
+const AGENT_MAX_TOKENS: u32 = 150;
+// least completion budget of structured summaries, which are several times
+// longer than the text ones.
+const STRUCTURED_MIN_TOKENS: u32 = 600;
+
+// shape of the summaries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    // TL;DR, contributions, method, results, limitations and tags, falling
+    // back to text when the model's output does not validate.
+    Structured
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "text" => Some(OutputFormat::Text),
+            "structured" | "json" => Some(OutputFormat::Structured),
+            _ => None
+        }
+    }
+}
+
+// model and inference parameters of the summarizing agent.
+#[derive(Debug, Clone)]
+pub struct AgentConfig {
+    // OpenAI model or Bedrock model id.
+    pub model: String,
+    pub prompt: PromptTemplate,
+    pub max_tokens: u32,
+    // provider defaults when unset.
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub output: OutputFormat
+}
+
+impl AgentConfig {
+    pub fn new(model: &str) -> Self {
+        AgentConfig {
+            model: model.to_string(),
+            prompt: PromptTemplate::default(),
+            max_tokens: AGENT_MAX_TOKENS,
+            temperature: None,
+            top_p: None,
+            output: OutputFormat::Text
+        }
+    }
+
+    // completion budget of a request for `output`.
+    pub fn completion_tokens(&self, output: OutputFormat) -> u32 {
+        match output {
+            OutputFormat::Text => self.max_tokens,
+            OutputFormat::Structured => self.max_tokens.max(STRUCTURED_MIN_TOKENS)
+        }
+    }
+
+    pub fn for_provider(provider: AgentProvider) -> Self {
+        match provider {
+            AgentProvider::OpenAI => Self::new("gpt-4o-mini"),
+            // temperature and top_p are left to the model, some reject
+            // requests that set both.
+            AgentProvider::Bedrock => Self::new("us.amazon.nova-lite-v1:0")
+        }
+    }
+
+    // all keys are optional: `AGENT_MODEL`, `AGENT_MAX_TOKENS`,
+    // `AGENT_TEMPERATURE`, `AGENT_TOP_P`, `AGENT_OUTPUT` (text, structured)
+    // and the prompt keys, see
+    // `prompt_from_lookup`. They are read from the env file in `AGENT_CONFIG`
+    // as well, the environment taking precedence.
+    pub fn from_env(provider: AgentProvider) -> Self {
+        Self::from_lookup(provider, agent_lookup())
+    }
+
+    fn from_lookup<F>(provider: AgentProvider, get: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>
+    {
+        let get = |key: &str| get(key).filter(|value| !value.trim().is_empty());
+        let mut config = Self::for_provider(provider);
+        if let Some(model) = get("AGENT_MODEL") {
+            config.model = model.trim().to_string();
+        }
+        config.prompt = prompt_from_lookup(&get);
+        if let Some(max_tokens) = get("AGENT_MAX_TOKENS") {
+            config.max_tokens = max_tokens.trim().parse()
+                .ok()
+                .filter(|max_tokens| *max_tokens > 0)
+                .unwrap_or_else(|| panic!("AGENT_MAX_TOKENS must be a positive integer"));
+        }
+        if let Some(temperature) = get("AGENT_TEMPERATURE") {
+            config.temperature = Some(parse_f32_in(&temperature, "AGENT_TEMPERATURE", 0.0, 2.0));
+        }
+        if let Some(top_p) = get("AGENT_TOP_P") {
+            config.top_p = Some(parse_f32_in(&top_p, "AGENT_TOP_P", 0.0, 1.0));
+        }
+        if let Some(output) = get("AGENT_OUTPUT") {
+            config.output = OutputFormat::from_name(output.trim())
+                .unwrap_or_else(|| panic!("unknown AGENT_OUTPUT: {}", output));
+        }
+        config
+    }
+}
+
+// the environment, then the env file in `AGENT_CONFIG`.
+fn agent_lookup() -> impl Fn(&str) -> Option<String> {
+    let file: HashMap<String, String> = match env::var("AGENT_CONFIG") {
+        Ok(path) => dotenvy::from_filename_iter(&path)
+            .unwrap_or_else(|e| panic!("failed to read AGENT_CONFIG {}: {}", path, e))
+            .map(|item| item.unwrap_or_else(|e| panic!("invalid AGENT_CONFIG {}: {}", path, e)))
+            .collect(),
+        Err(_) => HashMap::new()
+    };
+    move |key| env::var(key).ok().or_else(|| file.get(key).cloned())
+}
+
+// flavour of the OpenAI API.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OpenAIApiType {
+    #[default]
+    OpenAI,
+    // deployments addressed by `api_base`, `api_version` and `deployment`.
+    Azure
+}
+
+impl OpenAIApiType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "openai" => Some(OpenAIApiType::OpenAI),
+            "azure" => Some(OpenAIApiType::Azure),
+            _ => None
+        }
+    }
+}
+
+// where the OpenAI agent sends its requests. Any server with an
+// OpenAI-compatible chat completions API can be used through `api_base`,
+// e.g. Ollama, vLLM or the llama.cpp server, which need no API key.
+#[derive(Clone, Default)]
+pub struct OpenAIEndpoint {
+    pub api_type: OpenAIApiType,
+    // `https://api.openai.com/v1` when unset.
+    pub api_base: Option<String>,
+    pub api_key: Option<String>,
+    pub org_id: Option<String>,
+    pub project_id: Option<String>,
+    // Azure only, the deployment defaults to the agent's model.
+    pub api_version: Option<String>,
+    pub deployment: Option<String>
+}
+
+impl OpenAIEndpoint {
+    // all keys are optional: `OPENAI_API_TYPE` (openai, azure),
+    // `OPENAI_API_BASE`, `OPENAI_API_KEY` or `OPENAI_API_KEY_FILE`,
+    // `OPENAI_ORG_ID`, `OPENAI_PROJECT_ID`, and for Azure `OPENAI_API_VERSION`
+    // and `OPENAI_DEPLOYMENT`. Read from `AGENT_CONFIG` as well, like the
+    // `AgentConfig` keys.
+    pub fn from_env() -> Self {
+        Self::from_lookup(agent_lookup())
+    }
+
+    fn from_lookup<F>(get: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>
+    {
+        let get = |key: &str| get(key)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        let api_type = get("OPENAI_API_TYPE")
+            .map(|name| OpenAIApiType::from_name(&name)
+                .unwrap_or_else(|| panic!("unknown OPENAI_API_TYPE: {}", name)))
+            .unwrap_or_default();
+        let api_key = get("OPENAI_API_KEY").or_else(|| get("OPENAI_API_KEY_FILE")
+            .map(|path| fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("failed to read OPENAI_API_KEY_FILE {}: {}", path, e))
+                .trim()
+                .to_string()));
+        let endpoint = OpenAIEndpoint {
+            api_type,
+            api_base: get("OPENAI_API_BASE").map(|base| base.trim_end_matches('/').to_string()),
+            api_key,
+            org_id: get("OPENAI_ORG_ID"),
+            project_id: get("OPENAI_PROJECT_ID"),
+            api_version: get("OPENAI_API_VERSION"),
+            deployment: get("OPENAI_DEPLOYMENT")
+        };
+        if api_type == OpenAIApiType::Azure {
+            assert!(endpoint.api_base.is_some(), "OPENAI_API_BASE is required with OPENAI_API_TYPE=azure");
+            assert!(endpoint.api_version.is_some(), "OPENAI_API_VERSION is required with OPENAI_API_TYPE=azure");
+        }
+        endpoint
+    }
+}
+
+// `AGENT_PROMPT` picks a built-in prompt set, `summary-v1` by default.
+// `AGENT_SYSTEM_PROMPT` and `AGENT_USER_PROMPT` (or their `_FILE` variants)
+// replace its parts, making a prompt of its own that is recorded as
+// `AGENT_PROMPT_NAME` (`custom` by default) and `AGENT_PROMPT_VERSION`, with a
+// short hash of its text so that edits are told apart.
+fn prompt_from_lookup<F>(get: &F) -> PromptTemplate
+where
+    F: Fn(&str) -> Option<String>
+{
+    let base = match get("AGENT_PROMPT") {
+        Some(id) => PromptTemplate::builtin(id.trim())
+            .unwrap_or_else(|| panic!("unknown AGENT_PROMPT: {}", id)),
+        None => PromptTemplate::default()
+    };
+    let text = |key: &str| get(key).or_else(|| {
+        let key = format!("{}_FILE", key);
+        get(&key).map(|path| fs::read_to_string(path.trim())
+            .unwrap_or_else(|e| panic!("failed to read {} {}: {}", key, path, e))
+            .trim()
+            .to_string())
+    });
+    let (system, user) = (text("AGENT_SYSTEM_PROMPT"), text("AGENT_USER_PROMPT"));
+    if system.is_none() && user.is_none() {
+        return base;
+    }
+    let name = get("AGENT_PROMPT_NAME").unwrap_or_else(|| String::from("custom"));
+    let version = get("AGENT_PROMPT_VERSION")
+        .map(|version| version.trim().parse()
+            .unwrap_or_else(|_| panic!("Failed to parse AGENT_PROMPT_VERSION as u32")))
+        .unwrap_or(1);
+    PromptTemplate::new(
+        name.trim(),
+        version,
+        &system.unwrap_or(base.system),
+        &user.unwrap_or(base.user)
+    ).unwrap_or_else(|e| panic!("invalid prompt: {}", e))
+        .with_digest()
+}
+
+fn parse_f32_in(value: &str, key: &str, min: f32, max: f32) -> f32 {
+    let var: f32 = value.trim()
+        .parse()
+        .unwrap_or_else(|_| panic!("Failed to parse {} as f32", key));
+    assert!((min..=max).contains(&var), "{} must be within [{}, {}]", key, min, max);
+    var
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration
+}
+
+impl RetryConfig {
+    pub fn new(max_attempts: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        RetryConfig {
+            max_attempts,
+            base_delay,
+            max_delay
+        }
+    }
+
+    // reads `{prefix}_MAX_ATTEMPTS`, `{prefix}_RETRY_BASE_MS` and
+    // `{prefix}_RETRY_MAX_MS`, keeping the values of `default` for unset keys.
+    pub fn from_env(prefix: &str, default: Self) -> Self {
+        let max_attempts = get_optional_positive_u32_from_env(&format!("{}_MAX_ATTEMPTS", prefix))
+            .unwrap_or(default.max_attempts);
+        let base_delay = get_optional_positive_u32_from_env(&format!("{}_RETRY_BASE_MS", prefix))
+            .map(|ms| Duration::from_millis(ms as u64))
+            .unwrap_or(default.base_delay);
+        let max_delay = get_optional_positive_u32_from_env(&format!("{}_RETRY_MAX_MS", prefix))
+            .map(|ms| Duration::from_millis(ms as u64))
+            .unwrap_or(default.max_delay);
+        Self::new(max_attempts, base_delay, max_delay)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SummarizerConfig {
+    pub max_in_flight: usize,
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+    pub retry: RetryConfig
+}
+
+impl Default for SummarizerConfig {
+    fn default() -> Self {
+        SummarizerConfig {
+            max_in_flight: 8,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            retry: RetryConfig::new(4, Duration::from_secs(1), Duration::from_secs(60))
+        }
+    }
+}
+
+impl SummarizerConfig {
+    pub fn new(
+        max_in_flight: usize,
+        requests_per_minute: Option<u32>,
+        tokens_per_minute: Option<u32>,
+        retry: RetryConfig
+    ) -> Self {
+        SummarizerConfig {
+            max_in_flight,
+            requests_per_minute,
+            tokens_per_minute,
+            retry
+        }
+    }
+
+    // all keys are optional; unset limits fall back to the defaults.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let max_in_flight = get_optional_positive_u32_from_env("MAX_IN_FLIGHT")
+            .map(|v| v as usize)
+            .unwrap_or(default.max_in_flight);
+        let requests_per_minute = get_optional_positive_u32_from_env("REQUESTS_PER_MINUTE")
+            .or(default.requests_per_minute);
+        let tokens_per_minute = get_optional_positive_u32_from_env("TOKENS_PER_MINUTE")
+            .or(default.tokens_per_minute);
+        let retry = RetryConfig::from_env("AGENT", default.retry);
+        Self::new(max_in_flight, requests_per_minute, tokens_per_minute, retry)
+    }
+}
+
+fn get_optional_positive_u32_from_env(key: &str) -> Option<u32> {
+    let var: u32 = env::var(key).ok()?
+        .parse()
+        .unwrap_or_else(|_| {
+            eprintln!("Failed to parse {} as u32", key);
+            process::exit(1);
+        });
+    assert!(var > 0, "{} must be positive", key);
+    Some(var)
+}
+
+fn get_list_from_env(key: &str) -> Vec<String> {
+    env::var(key)
+        .unwrap_or_else(|_| panic!("{} not found in env", key))
+        .split_whitespace()
+        .map(String::from)
+        .collect()
+}
+
+fn default_source_retry() -> RetryConfig {
+    RetryConfig::new(4, Duration::from_secs(1), Duration::from_secs(60))
+}
+
+#[derive(Debug, Clone)]
+pub struct BiorxivConfig {
+    // subject collections with spaces as underscores, e.g. `cell_biology`.
+    // Empty for all of them.
+    pub categories: Vec<String>,
+    pub retry: RetryConfig
+}
+
+impl BiorxivConfig {
+    pub fn new(categories: Vec<String>) -> Self {
+        BiorxivConfig {
+            categories,
+            retry: default_source_retry()
+        }
+    }
+
+    // `prefix` is the server, `BIORXIV` or `MEDRXIV`: reads the optional
+    // `{prefix}_CATEGORIES` and the retry keys.
+    pub fn from_env(prefix: &str) -> Self {
+        let categories = env::var(format!("{}_CATEGORIES", prefix))
+            .map(|categories| categories.split_whitespace().map(String::from).collect())
+            .unwrap_or_default();
+        let mut config = Self::new(categories);
+        config.retry = RetryConfig::from_env(prefix, config.retry);
+        config
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AclConfig {
+    // Anthology collections, e.g. `2024.acl` or `2024.emnlp`.
+    pub collections: Vec<String>,
+    pub retry: RetryConfig
+}
+
+impl AclConfig {
+    pub fn new(collections: Vec<String>) -> Self {
+        AclConfig {
+            collections,
+            retry: default_source_retry()
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let mut config = Self::new(get_list_from_env("ACL_COLLECTIONS"));
+        config.retry = RetryConfig::from_env("ACL", config.retry);
+        config
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenReviewConfig {
+    // venue ids, e.g. `ICLR.cc/2025/Conference`.
+    pub venues: Vec<String>,
+    pub retry: RetryConfig
+}
+
+impl OpenReviewConfig {
+    pub fn new(venues: Vec<String>) -> Self {
+        OpenReviewConfig {
+            venues,
+            retry: default_source_retry()
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let mut config = Self::new(get_list_from_env("OPENREVIEW_VENUES"));
+        config.retry = RetryConfig::from_env("OPENREVIEW", config.retry);
+        config
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PubmedConfig {
+    // search term in PubMed syntax, e.g. `"large language models"[Title/Abstract]`.
+    pub term: String,
+    // raises the E-utilities limit from 3 to 10 requests per second.
+    pub api_key: Option<String>,
+    pub retry: RetryConfig
+}
+
+impl PubmedConfig {
+    pub fn new(term: &str) -> Self {
+        PubmedConfig {
+            term: term.to_string(),
+            api_key: None,
+            retry: default_source_retry()
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let term = env::var("PUBMED_TERM").expect("PUBMED_TERM not found in env");
+        let mut config = Self::new(&term);
+        config.api_key = env::var("NCBI_API_KEY").ok().filter(|key| !key.is_empty());
+        config.retry = RetryConfig::from_env("PUBMED", config.retry);
+        config
+    }
+}
+
+// what happens to papers that earlier runs already summarized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DedupMode {
+    // every fetched paper is summarized.
+    Off,
+    // papers in the seen index are dropped.
+    Skip,
+    // as `Skip`, but new versions whose abstract changed are summarized again
+    // and marked as updates.
+    Update
+}
+
+impl DedupMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "off" => Some(DedupMode::Off),
+            "skip" => Some(DedupMode::Skip),
+            "update" => Some(DedupMode::Update),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DedupConfig {
+    pub mode: DedupMode,
+    // seen index on the local disk, next to the outputs in S3 otherwise.
+    pub local_path: Option<String>
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self::new(DedupMode::Off)
+    }
+}
+
+impl DedupConfig {
+    pub fn new(mode: DedupMode) -> Self {
+        DedupConfig {
+            mode,
+            local_path: None
+        }
+    }
+
+    // `DEDUP` (off, skip, update) and `SEEN_INDEX_PATH` are optional.
+    pub fn from_env() -> Self {
+        let mode = env::var("DEDUP")
+            .map(|name| DedupMode::from_name(&name)
+                .unwrap_or_else(|| panic!("unknown DEDUP: {}", name)))
+            .unwrap_or(DedupMode::Off);
+        let mut config = Self::new(mode);
+        config.local_path = env::var("SEEN_INDEX_PATH").ok().filter(|path| !path.trim().is_empty());
+        config
+    }
+}
+
 #[derive(Debug)]
 pub struct NameConfig {
     pub bucket: String,
+    prefix: String,
     key: String
 }
 
@@ -73,6 +679,7 @@ impl NameConfig {
     pub fn new(bucket: &str, key: &str) -> Self {
         NameConfig {
             bucket: bucket.to_string(),
+            prefix: PROCESSED_FOLDER_PREFIX.to_string(),
             key: key.to_string()
         }
     }
@@ -82,7 +689,98 @@ impl NameConfig {
         Self::new(bucket, &key)
     }
 
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    // outputs for the papers submitted on `date`. The key only depends on the
+    // date, so that backfills can tell which days are already present.
+    pub fn for_date(&self, date: NaiveDate) -> Self {
+        NameConfig {
+            bucket: self.bucket.clone(),
+            prefix: self.prefix.clone(),
+            key: date.format("%y%m%d").to_string()
+        }
+    }
+
     pub fn jsonl_path(&self) -> String {
-        format!("{}/processed_{}.jsonl", PROCESSED_FOLDER_PREFIX, self.key)
+        format!("{}/processed_{}.jsonl", self.prefix, self.key)
+    }
+
+    // sidecar listing the papers of `jsonl_path` that failed to summarize.
+    pub fn failures_path(&self) -> String {
+        format!("{}/failed_{}.jsonl", self.prefix, self.key)
+    }
+
+    // papers summarized by any run under this prefix, shared across keys.
+    pub fn seen_path(&self) -> String {
+        format!("{}/seen.jsonl", self.prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_config_lookup() {
+        let values: HashMap<&str, &str> = HashMap::from([
+            ("AGENT_MODEL", "gpt-4.1-mini"),
+            ("AGENT_SYSTEM_PROMPT", "Summarize in one sentence."),
+            ("AGENT_USER_PROMPT", "{title}\n\n{abstract}"),
+            ("AGENT_PROMPT_VERSION", "3"),
+            ("AGENT_MAX_TOKENS", "80"),
+            ("AGENT_TOP_P", " "),
+            ("AGENT_OUTPUT", "structured")
+        ]);
+        let config = AgentConfig::from_lookup(AgentProvider::OpenAI, |key| {
+            values.get(key).map(|value| value.to_string())
+        });
+        assert_eq!(config.model, "gpt-4.1-mini");
+        assert_eq!(config.prompt.system, "Summarize in one sentence.");
+        assert_eq!(config.prompt.user, "{title}\n\n{abstract}");
+        let id = config.prompt.id();
+        assert!(id.starts_with("custom-v3-") && id.len() == "custom-v3-".len() + 16, "{}", id);
+        let edited = AgentConfig::from_lookup(AgentProvider::OpenAI, |key| match key {
+            // whitespace edits count as well.
+            "AGENT_USER_PROMPT" => Some(String::from("{title}\n{abstract}")),
+            _ => values.get(key).map(|value| value.to_string())
+        });
+        assert_ne!(edited.prompt.id(), id);
+        assert_eq!(config.max_tokens, 80);
+        assert_eq!(config.temperature, None);
+        assert_eq!(config.top_p, None);
+        assert_eq!(config.output, OutputFormat::Structured);
+        assert_eq!(config.completion_tokens(OutputFormat::Structured), STRUCTURED_MIN_TOKENS);
+
+        let config = AgentConfig::from_lookup(AgentProvider::Bedrock, |_| None);
+        assert_eq!(config.model, "us.amazon.nova-lite-v1:0");
+        assert_eq!(config.prompt, PromptTemplate::default());
+        assert_eq!(config.temperature, None);
+    }
+
+    #[test]
+    fn test_openai_endpoint_lookup() {
+        let values: HashMap<&str, &str> = HashMap::from([
+            ("OPENAI_API_BASE", "http://localhost:11434/v1/"),
+            ("OPENAI_ORG_ID", " ")
+        ]);
+        let endpoint = OpenAIEndpoint::from_lookup(|key| values.get(key).map(|value| value.to_string()));
+        assert_eq!(endpoint.api_type, OpenAIApiType::OpenAI);
+        assert_eq!(endpoint.api_base.as_deref(), Some("http://localhost:11434/v1"));
+        assert_eq!(endpoint.api_key, None);
+        assert_eq!(endpoint.org_id, None);
+
+        let values: HashMap<&str, &str> = HashMap::from([
+            ("OPENAI_API_TYPE", "Azure"),
+            ("OPENAI_API_BASE", "https://example.openai.azure.com"),
+            ("OPENAI_API_KEY", "key"),
+            ("OPENAI_API_VERSION", "2024-10-21")
+        ]);
+        let endpoint = OpenAIEndpoint::from_lookup(|key| values.get(key).map(|value| value.to_string()));
+        assert_eq!(endpoint.api_type, OpenAIApiType::Azure);
+        assert_eq!(endpoint.api_key.as_deref(), Some("key"));
+        assert_eq!(endpoint.deployment, None);
     }
 }
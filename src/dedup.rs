@@ -0,0 +1,243 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::ErrorKind
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{self, Error as JsonError};
+
+use crate::{
+    config::DedupMode,
+    hash,
+    model::{ArxivResult, ProcessedResult, Source},
+    storage::{S3Storage, StorageError}
+};
+
+// Papers summarized by earlier runs. Revised versions and cross-lists show up
+// again on later days; the index lets a run skip them, or summarize only the
+// versions whose abstract changed.
+
+/// Stable hash of an abstract, insensitive to whitespace changes.
+pub fn abstract_hash(text: &str) -> u64 {
+    hash::fnv1a(text.split_whitespace()
+        .flat_map(|word| word.bytes().chain(std::iter::once(b' '))))
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SeenEntry {
+    #[serde(default)]
+    pub source: Source,
+    pub id: String,
+    // latest summarized version.
+    pub version: u32,
+    // `abstract_hash` of that version.
+    pub hash: u64,
+    pub summarized: DateTime<Utc>
+}
+
+type SeenKey = (Source, String);
+
+#[derive(Debug, Default)]
+pub struct SeenIndex {
+    entries: HashMap<SeenKey, SeenEntry>
+}
+
+// papers left to summarize, with the keys of those that are updates.
+#[derive(Debug, Default)]
+pub struct DedupOutcome {
+    pub fresh: Vec<ArxivResult>,
+    pub updates: HashSet<SeenKey>,
+    pub skipped: usize
+}
+
+impl SeenIndex {
+    pub fn from_jsonl(text: &str) -> Result<Self, JsonError> {
+        let mut index = SeenIndex::default();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let entry: SeenEntry = serde_json::from_str(line)?;
+            index.entries.insert((entry.source, entry.id.clone()), entry);
+        }
+        Ok(index)
+    }
+
+    // sorted, so that the stored index diffs cleanly between runs.
+    pub fn to_jsonl(&self) -> Result<String, JsonError> {
+        let mut entries: Vec<&SeenEntry> = self.entries.values().collect();
+        entries.sort_by(|a, b| (a.source.to_string(), &a.id).cmp(&(b.source.to_string(), &b.id)));
+        let mut text = String::new();
+        for entry in entries {
+            text.push_str(&serde_json::to_string(entry)?);
+            text.push('\n');
+        }
+        Ok(text)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, source: Source, id: &str) -> Option<&SeenEntry> {
+        self.entries.get(&(source, id.to_string()))
+    }
+
+    /// Drops the papers of `data` that were summarized before. With
+    /// `DedupMode::Update`, later versions with a changed abstract are kept
+    /// and listed in `updates`. Duplicates within `data` are dropped as well.
+    pub fn filter(&self, data: Vec<ArxivResult>, mode: DedupMode) -> DedupOutcome {
+        let mut outcome = DedupOutcome::default();
+        let mut batch: HashSet<SeenKey> = HashSet::new();
+        for result in data {
+            let key = (result.source, result.id.clone());
+            if mode == DedupMode::Off {
+                outcome.fresh.push(result);
+                continue;
+            }
+            if !batch.insert(key.clone()) {
+                outcome.skipped += 1;
+                continue;
+            }
+            match self.entries.get(&key) {
+                None => outcome.fresh.push(result),
+                Some(seen) if mode == DedupMode::Update
+                    && result.version > seen.version
+                    && abstract_hash(&result.summary) != seen.hash => {
+                    outcome.updates.insert(key);
+                    outcome.fresh.push(result);
+                },
+                Some(_) => outcome.skipped += 1
+            }
+        }
+        outcome
+    }
+
+    /// Adds the summarized papers, replacing older versions.
+    pub fn record(&mut self, results: &[ProcessedResult]) {
+        let now = Utc::now();
+        for result in results {
+            let key = (result.source, result.id.clone());
+            if self.entries.get(&key).is_some_and(|seen| seen.version > result.version) {
+                continue;
+            }
+            self.entries.insert(key, SeenEntry {
+                source: result.source,
+                id: result.id.clone(),
+                version: result.version,
+                hash: abstract_hash(&result.raw_original),
+                summarized: now
+            });
+        }
+    }
+}
+
+/// Where the seen index is kept.
+#[derive(Debug, Clone)]
+pub enum SeenStore {
+    Local(String),
+    S3 {
+        bucket: String,
+        key: String
+    }
+}
+
+impl SeenStore {
+    /// The stored index, empty before the first run.
+    pub async fn load(&self, storage: &S3Storage) -> Result<SeenIndex, StorageError> {
+        let text = match self {
+            SeenStore::Local(path) => match fs::read_to_string(path) {
+                Ok(text) => Some(text),
+                Err(e) if e.kind() == ErrorKind::NotFound => None,
+                Err(e) => return Err(StorageError::from(e))
+            },
+            SeenStore::S3 { bucket, key } => storage.download_text(bucket, key).await?
+        };
+        match text {
+            Some(text) => SeenIndex::from_jsonl(&text)
+                .map_err(|e| StorageError::new(&format!("Invalid seen index: {}", e))),
+            None => Ok(SeenIndex::default())
+        }
+    }
+
+    pub async fn save(&self, storage: &S3Storage, index: &SeenIndex) -> Result<(), StorageError> {
+        let text = index.to_jsonl()
+            .map_err(|e| StorageError::new(&format!("serde_json error: {}", e)))?;
+        match self {
+            SeenStore::Local(path) => fs::write(path, text)?,
+            SeenStore::S3 { bucket, key } => {
+                storage.upload_text(bucket, key, &text).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn result(id: &str, version: u32, summary: &str) -> ArxivResult {
+        ArxivResult {
+            version,
+            ..ArxivResult::new(
+                id.to_string(),
+                format!("Paper {}", id),
+                summary.to_string(),
+                vec![],
+                Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap(),
+                format!("http://arxiv.org/abs/{}v{}", id, version))
+        }
+    }
+
+    fn index() -> SeenIndex {
+        let mut index = SeenIndex::default();
+        index.record(&[
+            ProcessedResult::from_result(result("2501.00001", 1, "First  abstract."), String::new()),
+            ProcessedResult::from_result(result("2501.00002", 1, "Second abstract."), String::new())
+        ]);
+        index
+    }
+
+    #[test]
+    fn test_filter_skip() {
+        let data = vec![
+            result("2501.00001", 2, "A rewritten abstract."),
+            result("2501.00003", 1, "Third abstract."),
+            result("2501.00003", 1, "Third abstract.")
+        ];
+        let outcome = index().filter(data, DedupMode::Skip);
+        assert_eq!(outcome.fresh.len(), 1);
+        assert_eq!(outcome.fresh[0].id, "2501.00003");
+        assert_eq!(outcome.skipped, 2);
+        assert!(outcome.updates.is_empty());
+    }
+
+    #[test]
+    fn test_filter_update() {
+        let data = vec![
+            // new version, same abstract up to whitespace.
+            result("2501.00001", 2, "First abstract.\n"),
+            result("2501.00002", 3, "Second abstract, revised."),
+            result("2501.00002", 1, "Second abstract.")
+        ];
+        let outcome = index().filter(data, DedupMode::Update);
+        assert_eq!(outcome.fresh.len(), 1);
+        assert_eq!(outcome.fresh[0].version, 3);
+        assert!(outcome.updates.contains(&(Source::Arxiv, "2501.00002".to_string())));
+        assert_eq!(outcome.skipped, 2);
+    }
+
+    #[test]
+    fn test_jsonl_roundtrip() {
+        let index = index();
+        let restored = SeenIndex::from_jsonl(&index.to_jsonl().unwrap()).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored.get(Source::Arxiv, "2501.00001"), index.get(Source::Arxiv, "2501.00001"));
+        assert_eq!(restored.get(Source::Arxiv, "2501.00001").unwrap().hash, abstract_hash("First abstract."));
+    }
+}
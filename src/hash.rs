@@ -0,0 +1,22 @@
+// FNV-1a, 64 bit. Stable across runs and Rust versions, unlike `DefaultHasher`,
+// so it can be stored with the results it identifies.
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+pub fn fnv1a<I: IntoIterator<Item = u8>>(bytes: I) -> u64 {
+    bytes.into_iter()
+        .fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a("".bytes()), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a".bytes()), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a("foobar".bytes()), 0x85944171f73967e8);
+    }
+}
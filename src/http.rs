@@ -0,0 +1,110 @@
+use std::time::{Duration, Instant};
+use reqwest::{
+    header::{self, HeaderMap, HeaderValue, RETRY_AFTER},
+    Client
+};
+use tokio::{sync::Mutex, time};
+
+use crate::{
+    config::RetryConfig,
+    parser::{self, ParserError},
+    retry
+};
+
+// identifies the scraper to the APIs, as arXiv asks of automated clients.
+pub(crate) const USER_AGENT: &str = concat!(
+    "paperscraper/", env!("CARGO_PKG_VERSION"), " (daily paper digest; reqwest)"
+);
+
+// HTTP client shared by the paper sources: keeps requests `request_interval`
+// apart, as most of the APIs ask for, and retries transient failures.
+#[derive(Debug)]
+pub(crate) struct HttpClient {
+    client: Client,
+    request_interval: Duration,
+    retry: RetryConfig,
+    // sent with every request, on top of the client's own defaults. Always
+    // holds a user agent, `USER_AGENT` unless set by the caller.
+    headers: HeaderMap,
+    // time of the last request.
+    last_request: Mutex<Option<Instant>>
+}
+
+impl HttpClient {
+    pub(crate) fn new(request_interval: Duration, retry: RetryConfig) -> Self {
+        HttpClient {
+            client: Client::new(),
+            request_interval,
+            retry,
+            headers: HeaderMap::from_iter([(header::USER_AGENT, HeaderValue::from_static(USER_AGENT))]),
+            last_request: Mutex::new(None)
+        }
+    }
+
+    /// Sends requests through `client`, e.g. one with its own timeouts or
+    /// proxy. Its default user agent is replaced by the headers'.
+    pub(crate) fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    // replaces the values of the headers in `headers`, the others are kept.
+    pub(crate) fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    async fn wait_turn(&self) {
+        let mut last_request = self.last_request.lock().await;
+        if let Some(last) = *last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.request_interval {
+                time::sleep(self.request_interval - elapsed).await;
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+
+    async fn fetch(&self, url: &str) -> Result<String, ParserError> {
+        let response = self.client.get(url)
+            .headers(self.headers.clone())
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = response.headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            // arXiv sends its error feed with a 400, its message says more
+            // than the status.
+            if status.is_client_error() {
+                if let Some(message) = parser::api_error(&response.text().await?) {
+                    return Err(ParserError::Api(message));
+                }
+            }
+            return Err(ParserError::HttpStatus { status: status.as_u16(), retry_after });
+        }
+        Ok(response.text().await?)
+    }
+
+    /// Body of `url`, retrying transient failures.
+    pub(crate) async fn get(&self, url: &str) -> Result<String, ParserError> {
+        let retry = &self.retry;
+        let mut attempt: u32 = 1;
+        loop {
+            self.wait_turn().await;
+            match self.fetch(url).await {
+                Err(e) if e.is_retryable() && attempt < retry.max_attempts => {
+                    let delay = retry::backoff(retry, attempt, e.retry_after());
+                    eprintln!("Retrying request in {:?} (attempt {}/{}): {}",
+                        delay, attempt, retry.max_attempts, e);
+                    time::sleep(delay).await;
+                    attempt += 1;
+                },
+                result => return result
+            }
+        }
+    }
+}
@@ -0,0 +1,355 @@
+// Normalizes the LaTeX that authors put in titles and abstracts: accents and
+// text macros become Unicode, citations and labels are dropped, and math
+// spans are kept as `$...$` so that markdown renderers can typeset them.
+
+// text commands whose argument is kept as is.
+const FORMATTING: &[&str] = &[
+    "emph", "textit", "textbf", "textsc", "texttt", "textrm", "textsf", "textup",
+    "textnormal", "text", "mbox", "underline", "url", "mathrm", "mathbf", "mathit"
+];
+// commands dropped together with their argument.
+const DROPPED: &[&str] = &[
+    "cite", "citep", "citet", "citealp", "citeauthor", "ref", "eqref", "autoref",
+    "cref", "Cref", "label", "footnote", "thanks"
+];
+
+/// `text` with LaTeX markup converted to Unicode, e.g.
+/// `Sch\"{o}dinger~\cite{x}` -> `Schödinger`. Math spans are kept verbatim,
+/// `\(...\)` and `\[...\]` as `$...$` and `$$...$$`. Whitespace is collapsed.
+pub fn normalize(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '$' => {
+                let display = chars.get(i + 1) == Some(&'$');
+                let delimiter = if display { "$$" } else { "$" };
+                match find_math_end(&chars, i + delimiter.len(), delimiter) {
+                    Some(end) => {
+                        push_math(&mut out, &chars[i + delimiter.len()..end], delimiter);
+                        i = end + delimiter.len();
+                    },
+                    None => {
+                        out.push('$');
+                        i += 1;
+                    }
+                }
+            },
+            '\\' => i = command(&chars, i, &mut out),
+            '{' | '}' => i += 1,
+            '~' => {
+                out.push(' ');
+                i += 1;
+            },
+            '-' => {
+                let run = chars[i..].iter().take_while(|c| **c == '-').count();
+                // only between spaces or digits, leaving `--flag` and `a--b`.
+                let spaced = |c: Option<&char>| c
+                    .is_some_and(|c| c.is_whitespace() || c.is_ascii_digit());
+                let dash = spaced(i.checked_sub(1).and_then(|j| chars.get(j)))
+                    && spaced(chars.get(i + run));
+                match run {
+                    2 if dash => out.push('–'),
+                    3 if dash => out.push('—'),
+                    _ => out.extend(&chars[i..i + run])
+                }
+                i += run;
+            },
+            '`' if chars.get(i + 1) == Some(&'`') => {
+                out.push('“');
+                i += 2;
+            },
+            '\'' if chars.get(i + 1) == Some(&'\'') => {
+                out.push('”');
+                i += 2;
+            },
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Handles the command starting with the backslash at `start`, returns the
+// position after it.
+fn command(chars: &[char], start: usize, out: &mut String) -> usize {
+    let Some(&next) = chars.get(start + 1) else {
+        out.push('\\');
+        return start + 1;
+    };
+    match next {
+        '%' | '&' | '_' | '#' | '$' | '{' | '}' => {
+            out.push(next);
+            start + 2
+        },
+        // line breaks and explicit spaces.
+        '\\' | ' ' | ',' | ';' | ':' | '!' => {
+            out.push(' ');
+            start + 2
+        },
+        '(' | '[' => {
+            let (close, delimiter) = if next == '(' { (')', "$") } else { (']', "$$") };
+            let end = (start + 2..chars.len().saturating_sub(1))
+                .find(|&j| chars[j] == '\\' && chars[j + 1] == close);
+            match end {
+                Some(end) => {
+                    push_math(out, &chars[start + 2..end], delimiter);
+                    end + 2
+                },
+                None => {
+                    out.push('\\');
+                    start + 1
+                }
+            }
+        },
+        '"' | '\'' | '`' | '^' | '~' | '=' | '.' => accent_command(chars, start + 2, next, out),
+        c if c.is_ascii_alphabetic() => {
+            let end = start + 1 + chars[start + 1..].iter()
+                .take_while(|c| c.is_ascii_alphabetic())
+                .count();
+            let name: String = chars[start + 1..end].iter().collect();
+            named_command(chars, start, end, &name, out)
+        },
+        _ => {
+            out.push('\\');
+            start + 1
+        }
+    }
+}
+
+fn named_command(chars: &[char], start: usize, end: usize, name: &str, out: &mut String) -> usize {
+    match name {
+        "c" | "v" | "u" | "H" | "r" | "k" => {
+            let mark = name.chars().next().unwrap();
+            return accent_command(chars, end, mark, out);
+        },
+        _ => ()
+    }
+    if let Some(letter) = special_letter(name) {
+        out.push_str(letter);
+        // `\ss{}` or `Stra\ss e`, the space only ends the command.
+        return skip_empty_group(chars, end);
+    }
+    if let Some(symbol) = symbol(name) {
+        out.push_str(symbol);
+        return skip_empty_group(chars, end);
+    }
+    if FORMATTING.contains(&name) {
+        // the braces of the argument are dropped by `normalize`.
+        return end;
+    }
+    if DROPPED.contains(&name) {
+        // `method~\cite{doe2024}.` -> `method.`
+        out.truncate(out.trim_end().len());
+        let mut i = end;
+        // optional arguments, e.g. `\citep[p.~3]{doe2024}`.
+        while let Some((_, after)) = read_group(chars, i, '[', ']') {
+            i = after;
+        }
+        return read_group(chars, i, '{', '}').map_or(end, |(_, after)| after);
+    }
+    if name == "href" {
+        // `\href{url}{text}` keeps the text.
+        return read_group(chars, end, '{', '}').map_or(end, |(_, after)| after);
+    }
+    // unknown commands are kept verbatim rather than losing their meaning.
+    chars[start..end].iter().for_each(|c| out.push(*c));
+    match read_group(chars, end, '{', '}') {
+        Some((group, after)) => {
+            out.push('{');
+            out.push_str(&group);
+            out.push('}');
+            after
+        },
+        None => end
+    }
+}
+
+// `\"o`, `\"{o}`, `\c c` or `\'{\i}`, with the accent character `mark`;
+// `start` is the position after the command.
+fn accent_command(chars: &[char], start: usize, mark: char, out: &mut String) -> usize {
+    let mut i = start;
+    // letter commands take their argument after a space, e.g. `\c c`.
+    if mark.is_ascii_alphabetic() {
+        while chars.get(i) == Some(&' ') {
+            i += 1;
+        }
+    }
+    let (base, after) = match chars.get(i) {
+        Some('{') => match read_group(chars, i, '{', '}') {
+            Some((group, after)) => (group, after),
+            None => return i
+        },
+        Some(&c) => (c.to_string(), i + 1),
+        None => return i
+    };
+    // dotless i and j, written `\i` and `\j` below accents.
+    let base = match base.trim() {
+        "\\i" => String::from("i"),
+        "\\j" => String::from("j"),
+        base => base.to_string()
+    };
+    match base.chars().next() {
+        Some(letter) if base.chars().count() == 1 => out.push_str(&accent(mark, letter)),
+        _ => out.push_str(&base)
+    }
+    after
+}
+
+// `base` with the accent of `mark`, precomposed when there is such a
+// character and followed by a combining mark otherwise.
+fn accent(mark: char, base: char) -> String {
+    let (combining, composed) = match mark {
+        '"' => ('\u{0308}', "aäeëiïoöuüyÿAÄEËIÏOÖUÜYŸ"),
+        '\'' => ('\u{0301}', "aáeéiíoóuúyýcćnńsśzźAÁEÉIÍOÓUÚYÝCĆNŃSŚZŹ"),
+        '`' => ('\u{0300}', "aàeèiìoòuùAÀEÈIÌOÒUÙ"),
+        '^' => ('\u{0302}', "aâeêiîoôuûAÂEÊIÎOÔUÛ"),
+        '~' => ('\u{0303}', "aãnñoõAÃNÑOÕ"),
+        '=' => ('\u{0304}', "aāeēiīoōuūAĀEĒIĪOŌUŪ"),
+        '.' => ('\u{0307}', "eėzżEĖZŻ"),
+        'c' => ('\u{0327}', "cçsşCÇSŞ"),
+        'v' => ('\u{030C}', "cčeěnňrřsšzžCČEĚNŇRŘSŠZŽ"),
+        'u' => ('\u{0306}', "aăgğAĂGĞ"),
+        'H' => ('\u{030B}', "oőuűOŐUŰ"),
+        'r' => ('\u{030A}', "aåuůAÅUŮ"),
+        'k' => ('\u{0328}', "aąeęAĄEĘ"),
+        _ => return base.to_string()
+    };
+    let pairs: Vec<char> = composed.chars().collect();
+    pairs.chunks(2)
+        .find(|pair| pair[0] == base)
+        .map(|pair| pair[1].to_string())
+        .unwrap_or_else(|| format!("{}{}", base, combining))
+}
+
+fn special_letter(name: &str) -> Option<&'static str> {
+    match name {
+        "ss" => Some("ß"),
+        "o" => Some("ø"),
+        "O" => Some("Ø"),
+        "ae" => Some("æ"),
+        "AE" => Some("Æ"),
+        "oe" => Some("œ"),
+        "OE" => Some("Œ"),
+        "aa" => Some("å"),
+        "AA" => Some("Å"),
+        "l" => Some("ł"),
+        "L" => Some("Ł"),
+        "i" => Some("ı"),
+        _ => None
+    }
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    match name {
+        "ldots" | "dots" | "textellipsis" => Some("…"),
+        "textendash" => Some("–"),
+        "textemdash" => Some("—"),
+        "textquotedblleft" => Some("“"),
+        "textquotedblright" => Some("”"),
+        "textasciitilde" => Some("~"),
+        "textbackslash" => Some("\\"),
+        "LaTeX" => Some("LaTeX"),
+        "TeX" => Some("TeX"),
+        _ => None
+    }
+}
+
+// position after `{}` or a single space following a command at `i`.
+fn skip_empty_group(chars: &[char], i: usize) -> usize {
+    match (chars.get(i), chars.get(i + 1)) {
+        (Some('{'), Some('}')) => i + 2,
+        (Some(' '), Some(c)) if c.is_alphabetic() => i + 1,
+        _ => i
+    }
+}
+
+// Content of the group opened by `open` at `i`, possibly after spaces, and
+// the position after its closing delimiter.
+fn read_group(chars: &[char], i: usize, open: char, close: char) -> Option<(String, usize)> {
+    let start = i + chars[i.min(chars.len())..].iter().take_while(|c| **c == ' ').count();
+    if chars.get(start) != Some(&open) {
+        return None;
+    }
+    let mut depth = 0;
+    for (j, c) in chars.iter().enumerate().skip(start) {
+        if *c == open {
+            depth += 1;
+        } else if *c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some((chars[start + 1..j].iter().collect(), j + 1));
+            }
+        }
+    }
+    None
+}
+
+// position of the `delimiter` closing a math span opened before `from`.
+fn find_math_end(chars: &[char], from: usize, delimiter: &str) -> Option<usize> {
+    let mut j = from;
+    while j < chars.len() {
+        match chars[j] {
+            '\\' => j += 2,
+            '$' if delimiter == "$" => return Some(j),
+            '$' if chars.get(j + 1) == Some(&'$') => return Some(j),
+            _ => j += 1
+        }
+    }
+    None
+}
+
+fn push_math(out: &mut String, content: &[char], delimiter: &str) {
+    let content: String = content.iter().collect();
+    out.push_str(delimiter);
+    out.push_str(content.trim());
+    out.push_str(delimiter);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accents_and_letters() {
+        assert_eq!(normalize(r#"Sch\"{o}dinger and {\"u}ber Gr\"obner"#), "Schödinger and über Gröbner");
+        assert_eq!(normalize(r"Erd\H{o}s, Ca\~{n}ete, Fran\c{c}ois, Ha\v{s}ek"), "Erdős, Cañete, François, Hašek");
+        assert_eq!(normalize(r"Stra\ss e, \O{}rsted, \L{}ukasz, Mart\'{\i}n"), "Straße, Ørsted, Łukasz, Martín");
+        // no precomposed character, followed by the combining mark.
+        assert_eq!(normalize(r"\v{q}"), "q\u{030C}");
+    }
+
+    #[test]
+    fn test_text_macros() {
+        assert_eq!(
+            normalize(r"We \emph{propose} a \textbf{new}~method\footnote{Code soon.} \citep[p.~3]{doe2024}."),
+            "We propose a new method."
+        );
+        assert_eq!(
+            normalize("``Large'' models --- 10--20\\% faster\\ldots{} see \\href{https://x.org}{our site}"),
+            "“Large” models — 10–20% faster… see our site"
+        );
+        assert_eq!(normalize(r"the \unknown{macro} stays"), r"the \unknown{macro} stays");
+        assert_eq!(
+            normalize("run --flag on a--b, ---- or pages 3 -- 5"),
+            "run --flag on a--b, ---- or pages 3 – 5"
+        );
+    }
+
+    #[test]
+    fn test_math_spans() {
+        assert_eq!(
+            normalize(r"$\mathcal{O}(n \log n)$ Attention with $k < 10$ Steps"),
+            r"$\mathcal{O}(n \log n)$ Attention with $k < 10$ Steps"
+        );
+        assert_eq!(
+            normalize(r"costs \( \alpha^2 \) and \[ x_{i} \] but \$5 and $$a--b$$"),
+            r"costs $\alpha^2$ and $$x_{i}$$ but $5 and $$a--b$$"
+        );
+        // an unmatched dollar is plain text.
+        assert_eq!(normalize(r"only $5 left"), "only $5 left");
+    }
+}
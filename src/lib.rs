@@ -1,6 +1,22 @@
+pub mod acl;
 pub mod agent;
+pub mod biorxiv;
 pub mod config;
+pub mod dedup;
+pub mod hash;
+pub mod latex;
+pub mod limiter;
 pub mod model;
+pub mod oai;
+pub mod openreview;
 pub mod parser;
+pub mod pipeline;
+pub mod prompt;
+pub mod pubmed;
+pub mod query;
+pub mod retry;
+pub mod rss;
+pub mod source;
 pub mod storage;
-mod prompt;
+pub mod structured;
+mod http;
@@ -0,0 +1,110 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant}
+};
+use tokio::time;
+
+// Requests-per-minute / tokens-per-minute limiter shared by all in-flight
+// summarization tasks of an agent. Each limit is a token bucket that refills
+// continuously; callers reserve their cost up front and sleep off any debt,
+// which keeps the order of requests roughly FIFO.
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests: Option<Mutex<Bucket>>,
+    tokens: Option<Mutex<Bucket>>
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: Option<u32>, tokens_per_minute: Option<u32>) -> Self {
+        RateLimiter {
+            requests: requests_per_minute.map(|rpm| Mutex::new(Bucket::per_minute(rpm))),
+            tokens: tokens_per_minute.map(|tpm| Mutex::new(Bucket::per_minute(tpm)))
+        }
+    }
+
+    pub fn unlimited() -> Self {
+        Self::new(None, None)
+    }
+
+    /// Waits until one request costing `tokens` tokens fits in both limits.
+    pub async fn acquire(&self, tokens: u32) {
+        let wait = self.reserve(tokens, Instant::now());
+        if !wait.is_zero() {
+            time::sleep(wait).await;
+        }
+    }
+
+    fn reserve(&self, tokens: u32, now: Instant) -> Duration {
+        let request_wait = self.requests.as_ref()
+            .map(|bucket| bucket.lock().unwrap().reserve(1.0, now))
+            .unwrap_or_default();
+        let token_wait = self.tokens.as_ref()
+            .map(|bucket| bucket.lock().unwrap().reserve(tokens as f64, now))
+            .unwrap_or_default();
+        request_wait.max(token_wait)
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    refill_per_sec: f64,
+    last: Instant
+}
+
+impl Bucket {
+    fn per_minute(limit: u32) -> Self {
+        let capacity = limit.max(1) as f64;
+        Bucket {
+            capacity,
+            available: capacity,
+            refill_per_sec: capacity / 60.0,
+            last: Instant::now()
+        }
+    }
+
+    // takes `amount` out of the bucket, possibly going into debt, and returns
+    // how long the caller has to wait for the debt to be paid back.
+    fn reserve(&mut self, amount: f64, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last = self.last.max(now);
+
+        // a single request larger than the whole bucket would never fit.
+        self.available -= amount.min(self.capacity);
+        if self.available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available / self.refill_per_sec)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_limit() {
+        let limiter = RateLimiter::new(Some(60), None);
+        let now = Instant::now();
+        for _ in 0..60 {
+            assert_eq!(limiter.reserve(0, now), Duration::ZERO);
+        }
+        // bucket is empty, the next request waits for one refill (1s at 60 rpm).
+        let wait = limiter.reserve(0, now);
+        assert!((wait.as_secs_f64() - 1.0).abs() < 1e-6, "unexpected wait: {:?}", wait);
+    }
+
+    #[test]
+    fn test_token_limit() {
+        let limiter = RateLimiter::new(None, Some(6000));
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(6000, now), Duration::ZERO);
+        let wait = limiter.reserve(1000, now + Duration::from_secs(5));
+        // 500 tokens refilled in 5s, 500 tokens of debt at 100 tokens/s.
+        assert!((wait.as_secs_f64() - 5.0).abs() < 1e-6, "unexpected wait: {:?}", wait);
+    }
+}
@@ -1,65 +1,315 @@
+use std::{fmt, sync::LazyLock};
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use regex::Regex;
 
-#[derive(Debug, Deserialize, Serialize)]
+use crate::{latex, structured::StructuredSummary};
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Author {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub affiliations: Vec<String>
+}
+
+impl Author {
+    pub fn new(name: String, affiliations: Vec<String>) -> Self {
+        Author {
+            name,
+            affiliations
+        }
+    }
+}
+
+/// Half-open `[from, to)` window of submission times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>
+}
+
+impl DateRange {
+    pub fn new(from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        DateRange {
+            from,
+            to
+        }
+    }
+
+    /// Whole days `[from, to)`, in UTC.
+    pub fn days(from: NaiveDate, to: NaiveDate) -> Self {
+        Self::new(
+            from.and_time(NaiveTime::MIN).and_utc(), 
+            to.and_time(NaiveTime::MIN).and_utc()
+        )
+    }
+
+    pub fn day(date: NaiveDate) -> Self {
+        Self::days(date, date + Duration::days(1))
+    }
+
+    /// The day ending `offset` days before the start of `date`'s day.
+    pub fn from_offset(date: DateTime<Utc>, offset: i64) -> Self {
+        let end = date.date_naive() - Duration::days(offset);
+        Self::days(end - Duration::days(1), end)
+    }
+
+    /// Splits the range into consecutive single days.
+    pub fn split_days(&self) -> Vec<DateRange> {
+        let mut days = Vec::new();
+        let mut date = self.from.date_naive();
+        while date.and_time(NaiveTime::MIN).and_utc() < self.to {
+            days.push(Self::day(date));
+            date += Duration::days(1);
+        }
+        days
+    }
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {})", self.from.format("%Y-%m-%d %H:%M"), self.to.format("%Y-%m-%d %H:%M"))
+    }
+}
+
+static ARXIV_ID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<id>.+?)(?:v(?P<version>\d+))?$").unwrap()
+});
+
+/// Splits an arXiv identifier or abstract url into the canonical id and the
+/// version, e.g. `http://arxiv.org/abs/2501.01234v2` -> (`2501.01234`, `Some(2)`).
+/// Old-style identifiers such as `hep-th/9901001v1` are supported as well.
+pub fn split_arxiv_id(id: &str) -> (String, Option<u32>) {
+    let id = id.trim();
+    let id = id.split_once("/abs/").map(|(_, id)| id).unwrap_or(id);
+    match ARXIV_ID.captures(id) {
+        Some(caps) => (
+            caps["id"].to_string(), 
+            caps.name("version").and_then(|v| v.as_str().parse().ok())
+        ),
+        None => (id.to_string(), None)
+    }
+}
+
+/// Splits an author string such as `A. One (MIT), B. Two and C. Three` into
+/// authors, taking parenthesized text as the affiliation.
+pub fn split_authors(authors: &str) -> Vec<Author> {
+    let authors = authors.split_whitespace().collect::<Vec<_>>().join(" ");
+    split_top_level(&authors, ",").iter()
+        .flat_map(|name| split_top_level(name, " and "))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .map(|name| match name.split_once(" (") {
+            Some((name, affiliation)) => Author::new(
+                name.trim().to_string(),
+                vec![affiliation.trim_end_matches(')').trim().to_string()]
+            ),
+            None => Author::new(name, vec![])
+        })
+        .collect()
+}
+
+// splits on `separator` outside of parentheses.
+fn split_top_level(text: &str, separator: &str) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    let mut start = 0;
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if depth == 0 && i >= start && text[i..].starts_with(separator) => {
+                parts.push(text[start..i].to_string());
+                start = i + separator.len();
+            },
+            _ => ()
+        }
+    }
+    parts.push(text[start..].to_string());
+    parts
+}
+
+/// Server a paper was fetched from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    #[default]
+    Arxiv,
+    Biorxiv,
+    Medrxiv,
+    AclAnthology,
+    OpenReview,
+    Pubmed
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Source::Arxiv => "arXiv",
+            Source::Biorxiv => "bioRxiv",
+            Source::Medrxiv => "medRxiv",
+            Source::AclAnthology => "ACL Anthology",
+            Source::OpenReview => "OpenReview",
+            Source::Pubmed => "PubMed"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Why a paper appears in a daily announcement, as reported by the RSS feeds.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnnounceType {
+    // first version, listed in its primary category.
+    New,
+    // new paper cross-listed from another category.
+    Cross,
+    // revised version.
+    Replace,
+    // revised version of a cross-listed paper.
+    ReplaceCross
+}
+
+impl AnnounceType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim() {
+            "new" => Some(AnnounceType::New),
+            "cross" => Some(AnnounceType::Cross),
+            "replace" => Some(AnnounceType::Replace),
+            "replace-cross" => Some(AnnounceType::ReplaceCross),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct ArxivResult {
-    pub id: usize,
+    // records of older runs predate other sources.
+    #[serde(default)]
+    pub source: Source,
+    // canonical identifier without version, e.g. `2501.01234` for arXiv or
+    // the DOI for bioRxiv. Together with `source` this is the key for dedup
+    // and joins across runs.
+    pub id: String,
+    pub version: u32,
+    // position of the paper in the fetched listing.
+    pub index: usize,
     pub title: String,
     pub summary: String,
-    pub authors: Vec<String>,
+    pub authors: Vec<Author>,
     pub published: DateTime<Utc>,
-    pub link: String
+    pub updated: DateTime<Utc>,
+    pub link: String,
+    pub primary_category: String,
+    pub categories: Vec<String>,
+    pub comment: Option<String>,
+    pub journal_ref: Option<String>,
+    pub doi: Option<String>,
+    // only known for papers read from the announcement feeds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub announce_type: Option<AnnounceType>
 }
 
 impl ArxivResult {
     pub fn new(
-        id: usize, 
+        id: String, 
         title: String, 
         summary: String, 
-        authors: Vec<String>, 
+        authors: Vec<Author>, 
         published: DateTime<Utc>, 
         link: String
     ) -> Self {
         ArxivResult {
             id,
+            version: 1,
             title,
             summary,
             authors,
             published,
-            link
+            updated: published,
+            link,
+            ..Default::default()
         }
     }
+
+    // e.g. `2501.01234v2`
+    pub fn versioned_id(&self) -> String {
+        format!("{}v{}", self.id, self.version)
+    }
+
+    // `title` and `summary` are kept as the source sent them; these are the
+    // variants with LaTeX converted to Unicode, see `latex::normalize`.
+    pub fn clean_title(&self) -> String {
+        latex::normalize(&self.title)
+    }
+
+    pub fn clean_summary(&self) -> String {
+        latex::normalize(&self.summary)
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct ProcessedResult {
-    pub id: usize,
+    #[serde(default)]
+    pub source: Source,
+    pub id: String,
+    pub version: u32,
+    pub index: usize,
+    // title and abstract with LaTeX converted to Unicode.
     pub title: String,
     pub original: String,
+    // as sent by the source, empty in records of older runs.
+    #[serde(default)]
+    pub raw_title: String,
+    #[serde(default)]
+    pub raw_original: String,
     pub summary: String,
-    pub authors: Vec<String>,
+    pub authors: Vec<Author>,
     pub published: DateTime<Utc>,
-    pub link: String
+    pub updated: DateTime<Utc>,
+    pub link: String,
+    pub primary_category: String,
+    pub categories: Vec<String>,
+    pub comment: Option<String>,
+    pub journal_ref: Option<String>,
+    pub doi: Option<String>,
+    // only known for papers read from the announcement feeds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub announce_type: Option<AnnounceType>,
+    // summarized again because a new version changed the abstract.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_update: bool,
+    // prompt that produced `summary`, e.g. `summary-v2`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+    // set in structured output mode, `summary` then holds its TL;DR.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured: Option<StructuredSummary>
 }
 
 impl ProcessedResult {
     pub fn new(
-        id: usize, 
+        id: String, 
         title: String, 
         original: String,
         summary: String, 
-        authors: Vec<String>, 
+        authors: Vec<Author>, 
         published: DateTime<Utc>, 
         link: String
     ) -> Self {
         ProcessedResult {
             id,
+            version: 1,
+            raw_title: title.clone(),
+            raw_original: original.clone(),
             title,
             original,
             summary,
             authors,
             published,
-            link
+            updated: published,
+            link,
+            ..Default::default()
         }
     }
 
@@ -67,14 +317,146 @@ impl ProcessedResult {
         original: ArxivResult,
         summary: String
     ) -> Self {
+        let (title, abstract_text) = (original.clean_title(), original.clean_summary());
         ProcessedResult {
+            source: original.source,
             id: original.id,
-            title: original.title,
-            original: original.summary,
+            version: original.version,
+            index: original.index,
+            title,
+            original: abstract_text,
+            raw_title: original.title,
+            raw_original: original.summary,
             summary,
             authors: original.authors,
             published: original.published,
-            link: original.link
+            updated: original.updated,
+            link: original.link,
+            primary_category: original.primary_category,
+            categories: original.categories,
+            comment: original.comment,
+            journal_ref: original.journal_ref,
+            doi: original.doi,
+            announce_type: original.announce_type,
+            is_update: false,
+            prompt_version: None,
+            structured: None
         }
     }
 }
+
+// why a paper could not be summarized, kept with its `FailedResult`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentErrorKind {
+    RateLimit,
+    Timeout,
+    Server,
+    ContentFilter,
+    BadRequest,
+    // structured output that does not match the schema.
+    InvalidOutput,
+    Other
+}
+
+impl AgentErrorKind {
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, 
+            AgentErrorKind::RateLimit | AgentErrorKind::Timeout | AgentErrorKind::Server)
+    }
+}
+
+impl fmt::Display for AgentErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AgentErrorKind::RateLimit => "rate limit",
+            AgentErrorKind::Timeout => "timeout",
+            AgentErrorKind::Server => "server error",
+            AgentErrorKind::ContentFilter => "content filter",
+            AgentErrorKind::BadRequest => "bad request",
+            AgentErrorKind::InvalidOutput => "invalid output",
+            AgentErrorKind::Other => "other"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// papers that could not be summarized, written next to the processed results
+// so that every fetched paper is accounted for.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FailedResult {
+    #[serde(default)]
+    pub source: Source,
+    pub id: String,
+    pub version: u32,
+    pub title: String,
+    pub link: String,
+    pub error_kind: AgentErrorKind,
+    pub error: String,
+    pub attempts: u32
+}
+
+impl FailedResult {
+    pub fn new(
+        id: String,
+        version: u32,
+        title: String,
+        link: String,
+        error_kind: AgentErrorKind,
+        error: String,
+        attempts: u32
+    ) -> Self {
+        FailedResult {
+            source: Source::default(),
+            id,
+            version,
+            title,
+            link,
+            error_kind,
+            error,
+            attempts
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SummarizeOutcome {
+    pub results: Vec<ProcessedResult>,
+    pub failures: Vec<FailedResult>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_arxiv_id() {
+        assert_eq!(split_arxiv_id("http://arxiv.org/abs/2501.01234v2"), ("2501.01234".to_string(), Some(2)));
+        assert_eq!(split_arxiv_id("hep-th/9901001v11"), ("hep-th/9901001".to_string(), Some(11)));
+        assert_eq!(split_arxiv_id("2501.01234"), ("2501.01234".to_string(), None));
+    }
+
+    #[test]
+    fn test_split_authors() {
+        assert_eq!(split_authors("A. One (Dept. of Physics and Astronomy), B. Two and\n C. Three"), vec![
+            Author::new("A. One".to_string(), vec!["Dept. of Physics and Astronomy".to_string()]),
+            Author::new("B. Two".to_string(), vec![]),
+            Author::new("C. Three".to_string(), vec![])
+        ]);
+    }
+
+    #[test]
+    fn test_date_range() {
+        let range = DateRange::days(
+            NaiveDate::from_ymd_opt(2024, 12, 30).unwrap(), 
+            NaiveDate::from_ymd_opt(2025, 1, 2).unwrap()
+        );
+        let days = range.split_days();
+        assert_eq!(days.len(), 3);
+        assert_eq!(days[0], DateRange::day(NaiveDate::from_ymd_opt(2024, 12, 30).unwrap()));
+        assert_eq!(days[2].to, range.to);
+
+        let date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap().and_hms_opt(13, 0, 0).unwrap().and_utc();
+        assert_eq!(DateRange::from_offset(date, 1), days[0]);
+    }
+}
@@ -0,0 +1,623 @@
+use std::collections::HashSet;
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use serde::Deserialize;
+
+use crate::{
+    config::ArxivConfig,
+    model::{split_arxiv_id, split_authors, ArxivResult, Author, DateRange, Source},
+    http::HttpClient,
+    parser::ParserError,
+    source::{clean, require_text, skip_malformed}
+};
+
+// Bulk harvesting through arXiv's OAI-PMH interface. See
+// https://info.arxiv.org/help/oa/index.html
+//
+// Unlike the search API, OAI-PMH selects records by datestamp, the day a
+// record last changed. A day's harvest therefore holds the papers announced
+// that day along with revised and re-classified ones.
+
+// archives that are top-level sets, all others live under `physics:`.
+const TOP_LEVEL_SETS: [&str; 7] = ["cs", "econ", "eess", "math", "q-bio", "q-fin", "stat"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum MetadataFormat {
+    // authors with affiliations, submission and last update dates. There is
+    // no version history, so every paper is taken as v1 and
+    // `DedupMode::Update` cannot tell revisions apart; use `ArxivRaw` for it.
+    Arxiv,
+    // full version history, authors as a single string.
+    #[default]
+    ArxivRaw
+}
+
+impl MetadataFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "arxiv" => Some(MetadataFormat::Arxiv),
+            "arxivraw" => Some(MetadataFormat::ArxivRaw),
+            _ => None
+        }
+    }
+
+    fn prefix(&self) -> &'static str {
+        match self {
+            MetadataFormat::Arxiv => "arXiv",
+            MetadataFormat::ArxivRaw => "arXivRaw"
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OaiHarvester {
+    config: ArxivConfig,
+    client: HttpClient,
+    format: MetadataFormat
+}
+
+impl Default for OaiHarvester {
+    fn default() -> Self {
+        Self::from_config(ArxivConfig::default())
+    }
+}
+
+impl OaiHarvester {
+    pub fn from_config(config: ArxivConfig) -> Self {
+        OaiHarvester {
+            client: HttpClient::new(config.request_interval, config.retry.clone()),
+            config,
+            format: MetadataFormat::default()
+        }
+    }
+
+    pub fn with_format(mut self, format: MetadataFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// The day `date_offset` days before today.
+    pub fn default_range(&self) -> DateRange {
+        DateRange::from_offset(Utc::now(), self.config.date_offset as i64)
+    }
+
+    // sets covering the configured categories, e.g. `cs.CL` -> `cs`,
+    // `hep-th` -> `physics:hep-th`.
+    fn sets(&self) -> Vec<String> {
+        let mut sets: Vec<String> = Vec::new();
+        for category in &self.config.categories {
+            let archive = category.split('.').next().unwrap_or(category);
+            let set = if TOP_LEVEL_SETS.contains(&archive) {
+                archive.to_string()
+            } else {
+                format!("physics:{}", archive)
+            };
+            if !sets.contains(&set) {
+                sets.push(set);
+            }
+        }
+        sets
+    }
+
+    // a set also holds the other categories of its archive.
+    fn matches_categories(&self, result: &ArxivResult) -> bool {
+        result.categories.iter().any(|category| self.config.categories.iter()
+            .any(|wanted| category == wanted || category.split('.').next() == Some(wanted)))
+    }
+
+    fn list_records_url(&self, set: &str, range: &DateRange) -> String {
+        // `from` and `until` are inclusive days.
+        let until = (range.to - Duration::seconds(1)).date_naive();
+        format!("{}?verb=ListRecords&metadataPrefix={}&from={}&until={}&set={}",
+            self.config.oai_url,
+            self.format.prefix(),
+            range.from.format("%Y-%m-%d"),
+            until.format("%Y-%m-%d"),
+            set)
+    }
+
+    fn resumption_url(&self, token: &str) -> String {
+        let token: String = token.bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    (byte as char).to_string()
+                },
+                _ => format!("%{:02X}", byte)
+            })
+            .collect();
+        format!("{}?verb=ListRecords&resumptionToken={}", self.config.oai_url, token)
+    }
+
+    /// Records of the configured categories with a datestamp within `range`,
+    /// at most NUM_ENTRIES x NUM_PAGES of them.
+    pub async fn get_arxiv_results(
+        &self,
+        range: &DateRange
+    ) -> Result<Vec<ArxivResult>, ParserError> {
+        let limit = (self.config.num_entries * self.config.num_pages) as usize;
+        let mut results: Vec<ArxivResult> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        for set in self.sets() {
+            let mut url = self.list_records_url(&set, range);
+            println!("using oai url: {}", url);
+            let mut page = 0;
+            loop {
+                let xml = self.client.get(&url).await?;
+                let parsed: OaiDocument = quick_xml::de::from_str(&xml)?;
+                if let Some(error) = parsed.error {
+                    if error.code == "noRecordsMatch" {
+                        break;
+                    }
+                    return Err(error.into());
+                }
+                let list = parsed.list_records.unwrap_or_default();
+                println!("set {}, page {}, records {}", set, page, list.records.len());
+                for record in list.records {
+                    let Some(result) = record_into_result(results.len(), record)? else {
+                        continue;
+                    };
+                    // cross-listed papers show up in several sets.
+                    if self.matches_categories(&result) && seen.insert(result.id.clone()) {
+                        results.push(result);
+                    }
+                }
+                if results.len() >= limit {
+                    eprintln!("Warning: harvest stopped at {} results (NUM_ENTRIES x NUM_PAGES), \
+                        later records are not fetched", limit);
+                    results.truncate(limit);
+                    return Ok(results);
+                }
+                // an empty token marks the last page.
+                match list.resumption_token.filter(|token| !token.value.trim().is_empty()) {
+                    Some(token) => {
+                        if let Some(size) = token.complete_list_size {
+                            println!("resuming at {:?} of {}", token.cursor, size);
+                        }
+                        url = self.resumption_url(token.value.trim());
+                    },
+                    None => break
+                }
+                page += 1;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Papers with the given arXiv ids, one `GetRecord` request each. Records
+    /// only exist per paper, so the latest version is returned regardless of
+    /// the requested one. Ids that do not exist are skipped.
+    pub async fn fetch_by_ids(&self, ids: &[&str]) -> Result<Vec<ArxivResult>, ParserError> {
+        let mut results: Vec<ArxivResult> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        for id in ids {
+            let url = format!("{}?verb=GetRecord&metadataPrefix={}&identifier=oai:arXiv.org:{}",
+                self.config.oai_url, self.format.prefix(), split_arxiv_id(id).0);
+            let xml = self.client.get(&url).await?;
+            let parsed: OaiDocument = quick_xml::de::from_str(&xml)?;
+            if let Some(error) = parsed.error {
+                if error.code == "idDoesNotExist" {
+                    eprintln!("Warning: {} not found", id);
+                    continue;
+                }
+                return Err(error.into());
+            }
+            let record = parsed.get_record.and_then(|get| get.record);
+            if let Some(result) = record
+                .map(|record| record_into_result(results.len(), record))
+                .transpose()?
+                .flatten()
+            {
+                if seen.insert(result.id.clone()) {
+                    results.push(result);
+                }
+            }
+        }
+        Ok(results)
+    }
+}
+
+// `None` for deleted records, which carry no metadata, and for malformed ones,
+// which are skipped rather than failing the whole harvest.
+fn record_into_result(index: usize, record: OaiRecord) -> Result<Option<ArxivResult>, ParserError> {
+    if record.header.status.as_deref() == Some("deleted") {
+        return Ok(None);
+    }
+    let Some(metadata) = record.metadata else {
+        return Ok(None);
+    };
+    let result = match (metadata.arxiv, metadata.arxiv_raw) {
+        (Some(arxiv), _) => arxiv.into_result(index),
+        (_, Some(raw)) => raw.into_result(index),
+        (None, None) => return Ok(None)
+    };
+    skip_malformed(result.and_then(require_text))
+}
+
+fn optional(value: Option<String>) -> Option<String> {
+    value.map(|value| clean(&value)).filter(|value| !value.is_empty())
+}
+
+fn parse_day(value: &str) -> Result<DateTime<Utc>, ParserError> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+        .map_err(|e| ParserError::Date { value: value.to_string(), source: e })
+}
+
+// e.g. `Mon, 2 Apr 2007 19:18:42 GMT`
+fn parse_version_date(value: &str) -> Result<DateTime<Utc>, ParserError> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| ParserError::Date { value: value.to_string(), source: e })
+}
+
+fn link(id: &str, version: u32) -> String {
+    format!("https://arxiv.org/abs/{}v{}", id, version)
+}
+
+impl ArxivMetadata {
+    // the `arXiv` format has no version history, so the version is only known
+    // for papers that were never revised.
+    fn into_result(self, index: usize) -> Result<ArxivResult, ParserError> {
+        let published = parse_day(&self.created)?;
+        let updated = match self.updated.as_deref() {
+            Some(updated) => parse_day(updated)?,
+            None => published
+        };
+        let categories: Vec<String> = self.categories.split_whitespace().map(String::from).collect();
+        let id = self.id.trim().to_string();
+        Ok(ArxivResult {
+            source: Source::Arxiv,
+            link: format!("https://arxiv.org/abs/{}", id),
+            id,
+            version: 1,
+            index,
+            title: clean(&self.title),
+            summary: clean(&self.summary),
+            authors: self.authors.authors.into_iter()
+                .map(|author| {
+                    let name = [author.forenames, Some(author.keyname), author.suffix]
+                        .into_iter()
+                        .flatten()
+                        .map(|part| clean(&part))
+                        .filter(|part| !part.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    Author::new(name, author.affiliations.iter().map(|a| clean(a)).collect())
+                })
+                .collect(),
+            published,
+            updated,
+            primary_category: categories.first().cloned().unwrap_or_default(),
+            categories,
+            comment: optional(self.comments),
+            journal_ref: optional(self.journal_ref),
+            doi: optional(self.doi),
+            announce_type: None
+        })
+    }
+}
+
+impl ArxivRawMetadata {
+    fn into_result(self, index: usize) -> Result<ArxivResult, ParserError> {
+        let first = self.versions.first()
+            .ok_or_else(|| ParserError::MalformedEntry(format!("{} has no versions", self.id.trim())))?;
+        let last = self.versions.last().unwrap_or(first);
+        let published = parse_version_date(&first.date)?;
+        let updated = parse_version_date(&last.date)?;
+        let version = last.version.trim_start_matches('v').parse().unwrap_or(1);
+        let categories: Vec<String> = self.categories.split_whitespace().map(String::from).collect();
+        let id = self.id.trim().to_string();
+        Ok(ArxivResult {
+            source: Source::Arxiv,
+            link: link(&id, version),
+            id,
+            version,
+            index,
+            title: clean(&self.title),
+            summary: clean(&self.summary),
+            authors: split_authors(&self.authors),
+            published,
+            updated,
+            primary_category: categories.first().cloned().unwrap_or_default(),
+            categories,
+            comment: optional(self.comments),
+            journal_ref: optional(self.journal_ref),
+            doi: optional(self.doi),
+            announce_type: None
+        })
+    }
+}
+
+impl From<OaiErrorField> for ParserError {
+    fn from(error: OaiErrorField) -> Self {
+        ParserError::Api(format!("{}: {}", error.code, clean(&error.message)))
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OaiDocument {
+    error: Option<OaiErrorField>,
+    #[serde(rename = "ListRecords")]
+    list_records: Option<ListRecords>,
+    #[serde(rename = "GetRecord")]
+    get_record: Option<GetRecord>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OaiErrorField {
+    #[serde(rename = "@code")]
+    code: String,
+    #[serde(rename = "$text")]
+    message: String
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ListRecords {
+    #[serde(rename = "record")]
+    records: Vec<OaiRecord>,
+    #[serde(rename = "resumptionToken")]
+    resumption_token: Option<ResumptionToken>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GetRecord {
+    record: Option<OaiRecord>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ResumptionToken {
+    #[serde(rename = "@cursor")]
+    cursor: Option<usize>,
+    #[serde(rename = "@completeListSize")]
+    complete_list_size: Option<usize>,
+    #[serde(rename = "$text")]
+    value: String
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OaiRecord {
+    header: OaiHeader,
+    metadata: Option<OaiMetadata>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OaiHeader {
+    #[serde(rename = "@status")]
+    status: Option<String>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OaiMetadata {
+    #[serde(rename = "arXiv")]
+    arxiv: Option<ArxivMetadata>,
+    #[serde(rename = "arXivRaw")]
+    arxiv_raw: Option<ArxivRawMetadata>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ArxivMetadata {
+    id: String,
+    created: String,
+    updated: Option<String>,
+    authors: AuthorsField,
+    title: String,
+    categories: String,
+    comments: Option<String>,
+    #[serde(rename = "journal-ref")]
+    journal_ref: Option<String>,
+    doi: Option<String>,
+    #[serde(rename = "abstract")]
+    summary: String
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AuthorsField {
+    #[serde(rename = "author")]
+    authors: Vec<OaiAuthor>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OaiAuthor {
+    keyname: String,
+    forenames: Option<String>,
+    suffix: Option<String>,
+    #[serde(rename = "affiliation")]
+    affiliations: Vec<String>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ArxivRawMetadata {
+    id: String,
+    #[serde(rename = "version")]
+    versions: Vec<VersionField>,
+    title: String,
+    authors: String,
+    categories: String,
+    comments: Option<String>,
+    #[serde(rename = "journal-ref")]
+    journal_ref: Option<String>,
+    doi: Option<String>,
+    #[serde(rename = "abstract")]
+    summary: String
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct VersionField {
+    #[serde(rename = "@version")]
+    version: String,
+    date: String
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const LIST_RECORDS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+    <OAI-PMH xmlns="http://www.openarchives.org/OAI/2.0/">
+      <responseDate>2025-01-04T10:00:00Z</responseDate>
+      <request verb="ListRecords" metadataPrefix="arXivRaw" set="cs">http://oaipmh.arxiv.org/oai</request>
+      <ListRecords>
+        <record>
+          <header>
+            <identifier>oai:arXiv.org:2501.01234</identifier>
+            <datestamp>2025-01-03</datestamp>
+            <setSpec>cs</setSpec>
+          </header>
+          <metadata>
+            <arXivRaw xmlns="http://arxiv.org/OAI/arXivRaw/">
+              <id>2501.01234</id>
+              <submitter>Jane Doe</submitter>
+              <version version="v1"><date>Thu, 2 Jan 2025 18:59:59 GMT</date><size>120kb</size></version>
+              <version version="v2"><date>Fri, 3 Jan 2025 10:00:00 GMT</date><size>121kb</size></version>
+              <title>A   Paper
+                Title</title>
+              <authors>Jane Doe (MIT, CSAIL), John Roe and Max Mustermann</authors>
+              <categories>cs.CL cs.AI</categories>
+              <comments>12 pages</comments>
+              <journal-ref>ACL 2025</journal-ref>
+              <license>http://creativecommons.org/licenses/by/4.0/</license>
+              <abstract>  An abstract.
+              </abstract>
+            </arXivRaw>
+          </metadata>
+        </record>
+        <record>
+          <header status="deleted">
+            <identifier>oai:arXiv.org:2501.00001</identifier>
+            <datestamp>2025-01-03</datestamp>
+          </header>
+        </record>
+        <resumptionToken cursor="0" completeListSize="1234">6162373|1001</resumptionToken>
+      </ListRecords>
+    </OAI-PMH>"#;
+
+    const GET_RECORD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+    <OAI-PMH xmlns="http://www.openarchives.org/OAI/2.0/">
+      <GetRecord>
+        <record>
+          <header>
+            <identifier>oai:arXiv.org:hep-th/9901001</identifier>
+            <datestamp>2008-02-03</datestamp>
+          </header>
+          <metadata>
+            <arXiv xmlns="http://arxiv.org/OAI/arXiv/">
+              <id>hep-th/9901001</id>
+              <created>1999-01-04</created>
+              <updated>1999-02-01</updated>
+              <authors>
+                <author><keyname>Doe</keyname><forenames>Jane</forenames><affiliation>MIT</affiliation></author>
+                <author><keyname>Roe</keyname><forenames>John</forenames><suffix>Jr</suffix></author>
+              </authors>
+              <title>An Old Paper</title>
+              <categories>hep-th gr-qc</categories>
+              <doi>10.1234/abcd</doi>
+              <abstract>Old abstract.</abstract>
+            </arXiv>
+          </metadata>
+        </record>
+      </GetRecord>
+    </OAI-PMH>"#;
+
+    #[test]
+    fn test_list_records() {
+        let harvester = OaiHarvester::default();
+        let document: OaiDocument = quick_xml::de::from_str(LIST_RECORDS).unwrap();
+        let list = document.list_records.unwrap();
+        let token = list.resumption_token.unwrap();
+        assert_eq!(token.value, "6162373|1001");
+        assert_eq!(token.complete_list_size, Some(1234));
+        assert_eq!(harvester.resumption_url(&token.value),
+            "https://oaipmh.arxiv.org/oai?verb=ListRecords&resumptionToken=6162373%7C1001");
+
+        // a record without versions is skipped along with the deleted one.
+        let mut records = list.records;
+        records.push(OaiRecord {
+            metadata: Some(OaiMetadata {
+                arxiv_raw: Some(ArxivRawMetadata { id: String::from("2501.00002"), ..Default::default() }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let results: Vec<ArxivResult> = records.into_iter()
+            .filter_map(|record| record_into_result(0, record).unwrap())
+            .collect();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.id, "2501.01234");
+        assert_eq!(result.version, 2);
+        assert_eq!(result.title, "A Paper Title");
+        assert_eq!(result.summary, "An abstract.");
+        assert_eq!(result.authors, vec![
+            Author::new("Jane Doe".to_string(), vec!["MIT, CSAIL".to_string()]),
+            Author::new("John Roe".to_string(), vec![]),
+            Author::new("Max Mustermann".to_string(), vec![])
+        ]);
+        assert_eq!(result.published, Utc.with_ymd_and_hms(2025, 1, 2, 18, 59, 59).unwrap());
+        assert_eq!(result.updated, Utc.with_ymd_and_hms(2025, 1, 3, 10, 0, 0).unwrap());
+        assert_eq!(result.link, "https://arxiv.org/abs/2501.01234v2");
+        assert_eq!(result.primary_category, "cs.CL");
+        assert_eq!(result.comment.as_deref(), Some("12 pages"));
+        assert_eq!(result.journal_ref.as_deref(), Some("ACL 2025"));
+        assert_eq!(result.doi, None);
+        assert!(harvester.matches_categories(result));
+    }
+
+    #[test]
+    fn test_get_record() {
+        let harvester = OaiHarvester::default();
+        let document: OaiDocument = quick_xml::de::from_str(GET_RECORD).unwrap();
+        let record = document.get_record.and_then(|get| get.record).unwrap();
+        let result = record_into_result(0, record).unwrap().unwrap();
+        assert_eq!(result.id, "hep-th/9901001");
+        assert_eq!(result.authors, vec![
+            Author::new("Jane Doe".to_string(), vec!["MIT".to_string()]),
+            Author::new("John Roe Jr".to_string(), vec![])
+        ]);
+        assert_eq!(result.published, Utc.with_ymd_and_hms(1999, 1, 4, 0, 0, 0).unwrap());
+        assert_eq!(result.updated, Utc.with_ymd_and_hms(1999, 2, 1, 0, 0, 0).unwrap());
+        assert_eq!(result.primary_category, "hep-th");
+        assert_eq!(result.doi.as_deref(), Some("10.1234/abcd"));
+        assert!(!harvester.matches_categories(&result));
+    }
+
+    #[test]
+    fn test_requests() {
+        let config = ArxivConfig::new(50, 10, 1, vec![
+            "cs.CL".to_string(), "cs.AI".to_string(), "hep-th".to_string()
+        ]);
+        let harvester = OaiHarvester::from_config(config);
+        assert_eq!(harvester.sets(), vec!["cs", "physics:hep-th"]);
+
+        let range = DateRange::days(
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 1, 3).unwrap()
+        );
+        assert_eq!(harvester.list_records_url("cs", &range), concat!(
+            "https://oaipmh.arxiv.org/oai?verb=ListRecords&metadataPrefix=arXivRaw",
+            "&from=2025-01-01&until=2025-01-02&set=cs"
+        ));
+
+        let config = ArxivConfig { oai_url: String::from("http://localhost:8080/oai"), ..Default::default() };
+        assert!(OaiHarvester::from_config(config).list_records_url("cs", &range)
+            .starts_with("http://localhost:8080/oai?verb=ListRecords"));
+
+        let document: OaiDocument = quick_xml::de::from_str(
+            r#"<OAI-PMH><error code="noRecordsMatch">No records</error></OAI-PMH>"#
+        ).unwrap();
+        assert_eq!(document.error.unwrap().code, "noRecordsMatch");
+    }
+}
@@ -0,0 +1,260 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::{
+    config::OpenReviewConfig,
+    http::HttpClient,
+    model::{ArxivResult, Author, DateRange, Source},
+    parser::ParserError,
+    source::{clean, previous_day, require_text, skip_malformed, PaperSource}
+};
+
+// Accepted papers of OpenReview venues, through the API v2. See
+// https://docs.openreview.net/reference/api-v2
+//
+// Notes are dated by their publication date, so a venue's papers show up on
+// the day its decisions were made public.
+
+const OPENREVIEW_API_URL: &str = "https://api2.openreview.net/notes";
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+// largest page the API serves.
+const PAGE_SIZE: usize = 1000;
+
+#[derive(Debug)]
+pub struct OpenReview {
+    config: OpenReviewConfig,
+    client: HttpClient
+}
+
+impl OpenReview {
+    pub fn from_config(config: OpenReviewConfig) -> Self {
+        OpenReview {
+            client: HttpClient::new(REQUEST_INTERVAL, config.retry.clone()),
+            config
+        }
+    }
+
+    fn notes_url(params: &[(&str, &str)]) -> String {
+        Url::parse_with_params(OPENREVIEW_API_URL, params)
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| OPENREVIEW_API_URL.to_string())
+    }
+
+    async fn get_notes(&self, url: &str) -> Result<NotesResponse, ParserError> {
+        let body = self.client.get(url).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+}
+
+#[async_trait]
+impl PaperSource for OpenReview {
+    fn source(&self) -> Source {
+        Source::OpenReview
+    }
+
+    fn default_range(&self) -> DateRange {
+        previous_day()
+    }
+
+    async fn fetch(&self, range: &DateRange) -> Result<Vec<ArxivResult>, ParserError> {
+        let mut results: Vec<ArxivResult> = Vec::new();
+        for venue in &self.config.venues {
+            let mut offset: usize = 0;
+            loop {
+                // newest changes first. Publishing a note modifies it, so no
+                // note modified before `from` was published after it; the
+                // creation date would miss submissions published months later.
+                let url = Self::notes_url(&[
+                    ("content.venueid", venue),
+                    ("sort", "tmdate:desc"),
+                    ("limit", &PAGE_SIZE.to_string()),
+                    ("offset", &offset.to_string())
+                ]);
+                println!("using openreview url: {}", url);
+                let response = self.get_notes(&url).await?;
+                if response.notes.is_empty() {
+                    break;
+                }
+                offset += response.notes.len();
+                let past_range = response.notes.iter()
+                    .filter_map(|note| note.tmdate.or(note.mdate))
+                    .min()
+                    .is_some_and(|oldest| from_millis(oldest) < range.from);
+                for note in response.notes {
+                    let Some(result) = skip_malformed(note.into_result(results.len()))? else {
+                        continue;
+                    };
+                    if result.published >= range.from && result.published < range.to {
+                        results.push(result);
+                    }
+                }
+                if past_range || offset >= response.count.unwrap_or(0) {
+                    break;
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Papers by note id, the `id` in `openreview.net/forum?id=...`.
+    async fn fetch_by_ids(&self, ids: &[&str]) -> Result<Vec<ArxivResult>, ParserError> {
+        let mut results: Vec<ArxivResult> = Vec::new();
+        for id in ids {
+            let response = self.get_notes(&Self::notes_url(&[("id", id.trim())])).await?;
+            if response.notes.is_empty() {
+                eprintln!("Warning: {} not found", id);
+            }
+            for note in response.notes {
+                if let Some(result) = skip_malformed(note.into_result(results.len()))? {
+                    results.push(result);
+                }
+            }
+        }
+        Ok(results)
+    }
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+impl Note {
+    fn into_result(self, index: usize) -> Result<ArxivResult, ParserError> {
+        // publication date, falling back to when the note went public or was
+        // created.
+        let published = from_millis(self.pdate.or(self.odate).or(self.cdate).unwrap_or_default());
+        let updated = self.mdate.map(from_millis).unwrap_or(published);
+        let content = self.content;
+        let venue = content.venue.map(|field| clean(&field.value)).filter(|venue| !venue.is_empty());
+        require_text(ArxivResult {
+            source: Source::OpenReview,
+            link: format!("https://openreview.net/forum?id={}", self.forum.as_deref().unwrap_or(&self.id)),
+            id: self.id,
+            version: 1,
+            index,
+            title: content.title.map(|field| clean(&field.value)).unwrap_or_default(),
+            summary: content.summary.map(|field| clean(&field.value)).unwrap_or_default(),
+            authors: content.authors
+                .map(|field| field.value.iter()
+                    .map(|name| Author::new(clean(name), vec![]))
+                    .collect())
+                .unwrap_or_default(),
+            published,
+            updated,
+            // the venue is a decision such as `ICLR 2025 Poster`, kept as
+            // the journal reference; notes have no subject category.
+            primary_category: String::new(),
+            categories: content.keywords
+                .map(|field| field.value.iter().map(|keyword| clean(keyword)).collect())
+                .unwrap_or_default(),
+            comment: None,
+            journal_ref: venue,
+            doi: None,
+            announce_type: None
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct NotesResponse {
+    notes: Vec<Note>,
+    count: Option<usize>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Note {
+    id: String,
+    forum: Option<String>,
+    cdate: Option<i64>,
+    odate: Option<i64>,
+    pdate: Option<i64>,
+    mdate: Option<i64>,
+    // true modification date, including changes made by the venue.
+    tmdate: Option<i64>,
+    content: NoteContent
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct NoteContent {
+    title: Option<ContentField<String>>,
+    authors: Option<ContentField<Vec<String>>>,
+    #[serde(rename = "abstract")]
+    summary: Option<ContentField<String>>,
+    keywords: Option<ContentField<Vec<String>>>,
+    venue: Option<ContentField<String>>
+}
+
+// API v2 wraps every content value, e.g. `"title": {"value": "..."}`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ContentField<T: Default> {
+    value: T
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const NOTES: &str = r#"{
+        "notes": [{
+            "id": "abc123",
+            "forum": "abc123",
+            "number": 42,
+            "cdate": 1727000000000,
+            "pdate": 1737936000000,
+            "mdate": 1740000000000,
+            "tmdate": 1740000000000,
+            "content": {
+                "title": {"value": "A  Venue Paper"},
+                "authors": {"value": ["Jane Doe", "John Roe"]},
+                "authorids": {"value": ["~Jane_Doe1", "~John_Roe1"]},
+                "abstract": {"value": "An abstract.\n"},
+                "keywords": {"value": ["language models", "retrieval"]},
+                "venue": {"value": "ICLR 2025 Poster"},
+                "venueid": {"value": "ICLR.cc/2025/Conference"},
+                "pdf": {"value": "/pdf/0123456789abcdef.pdf"}
+            }
+        }],
+        "count": 1
+    }"#;
+
+    #[test]
+    fn test_notes_parsing() {
+        let response: NotesResponse = serde_json::from_str(NOTES).unwrap();
+        assert_eq!(response.count, Some(1));
+        assert_eq!(response.notes[0].tmdate, Some(1740000000000));
+        let result = response.notes.into_iter().next().unwrap().into_result(0).unwrap();
+        assert_eq!(result.source, Source::OpenReview);
+        assert_eq!(result.id, "abc123");
+        assert_eq!(result.title, "A Venue Paper");
+        assert_eq!(result.summary, "An abstract.");
+        assert_eq!(result.authors, vec![
+            Author::new("Jane Doe".to_string(), vec![]),
+            Author::new("John Roe".to_string(), vec![])
+        ]);
+        assert_eq!(result.published, Utc.with_ymd_and_hms(2025, 1, 27, 0, 0, 0).unwrap());
+        assert_eq!(result.link, "https://openreview.net/forum?id=abc123");
+        assert_eq!(result.primary_category, "");
+        assert_eq!(result.categories, vec!["language models", "retrieval"]);
+
+        // withdrawn and placeholder notes may have no abstract.
+        let note = Note { id: String::from("empty"), ..Default::default() };
+        assert!(matches!(note.into_result(0), Err(ParserError::MalformedEntry(_))));
+    }
+
+    #[test]
+    fn test_notes_url() {
+        assert_eq!(
+            OpenReview::notes_url(&[("content.venueid", "ICLR.cc/2025/Conference"), ("offset", "0")]),
+            "https://api2.openreview.net/notes?content.venueid=ICLR.cc%2F2025%2FConference&offset=0"
+        );
+    }
+}
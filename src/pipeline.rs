@@ -0,0 +1,193 @@
+use std::{
+    error::Error as StdError,
+    fmt
+};
+
+use crate::{
+    agent::Summarizer,
+    config::{DedupConfig, DedupMode, NameConfig},
+    dedup::SeenStore,
+    model::{ArxivResult, DateRange, SummarizeOutcome},
+    parser::ParserError,
+    source::PaperSource,
+    storage::{S3Storage, StorageError}
+};
+
+// Fetch -> summarize -> upload, shared by the binaries.
+pub struct Pipeline {
+    // combined into one digest, the first one is the primary source.
+    sources: Vec<Box<dyn PaperSource>>,
+    summarizer: Box<dyn Summarizer>,
+    storage: S3Storage,
+    names: NameConfig,
+    dedup: DedupConfig
+}
+
+impl Pipeline {
+    // fails without sources.
+    pub fn new(
+        sources: Vec<Box<dyn PaperSource>>,
+        summarizer: Box<dyn Summarizer>,
+        storage: S3Storage,
+        names: NameConfig
+    ) -> Result<Self, PipelineError> {
+        if sources.is_empty() {
+            return Err(PipelineError::new("pipeline needs at least one source"));
+        }
+        Ok(Pipeline {
+            sources,
+            summarizer,
+            storage,
+            names,
+            dedup: DedupConfig::default()
+        })
+    }
+
+    /// Skips papers that earlier runs summarized, see `DedupMode`.
+    pub fn with_dedup(mut self, dedup: DedupConfig) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Default range of the primary source.
+    pub fn default_range(&self) -> DateRange {
+        self.sources[0].default_range()
+    }
+
+    async fn fetch(&self, range: &DateRange) -> Result<Vec<ArxivResult>, PipelineError> {
+        let mut data: Vec<ArxivResult> = Vec::new();
+        for source in &self.sources {
+            let results = source.fetch(range).await?;
+            println!("{}: {} results", source.source(), results.len());
+            data.extend(results);
+        }
+        // positions in the combined listing.
+        data.iter_mut()
+            .enumerate()
+            .for_each(|(index, result)| result.index = index);
+        Ok(data)
+    }
+
+    /// Summarizes the papers submitted in `range` and uploads them under
+    /// `names`. Empty outputs are uploaded as well, marking the range as done.
+    pub async fn run(&self, range: &DateRange, names: &NameConfig) -> Result<(), PipelineError> {
+        println!("processing {}", range);
+        let data = self.fetch(range).await?;
+        println!("# results: {}", data.len());
+        self.process(data, names).await
+    }
+
+    /// Summarizes hand-picked papers by their id in the primary source,
+    /// uploaded under the pipeline's default names.
+    pub async fn run_ids(&self, ids: &[&str]) -> Result<(), PipelineError> {
+        println!("processing {} ids", ids.len());
+        let data = self.sources[0].fetch_by_ids(ids).await?;
+        println!("# results: {}", data.len());
+        self.process(data, &self.names).await
+    }
+
+    fn seen_store(&self, names: &NameConfig) -> SeenStore {
+        match &self.dedup.local_path {
+            Some(path) => SeenStore::Local(path.clone()),
+            None => SeenStore::S3 {
+                bucket: names.bucket.clone(),
+                key: names.seen_path()
+            }
+        }
+    }
+
+    async fn process(&self, data: Vec<ArxivResult>, names: &NameConfig) -> Result<(), PipelineError> {
+        if self.dedup.mode == DedupMode::Off {
+            let outcome = self.summarizer.summarize(data).await;
+            return self.upload(&outcome, names).await;
+        }
+        let store = self.seen_store(names);
+        let mut index = store.load(&self.storage).await?;
+        let dedup = index.filter(data, self.dedup.mode);
+        println!("skipping {} papers summarized before, {} updates",
+            dedup.skipped, dedup.updates.len());
+        let mut outcome = self.summarizer.summarize(dedup.fresh).await;
+        outcome.results.iter_mut()
+            .filter(|result| dedup.updates.contains(&(result.source, result.id.clone())))
+            .for_each(|result| result.is_update = true);
+        self.upload(&outcome, names).await?;
+        // only summarized papers count as seen, failures are retried by later
+        // runs.
+        index.record(&outcome.results);
+        store.save(&self.storage, &index).await?;
+        println!("{} papers in the seen index", index.len());
+        Ok(())
+    }
+
+    async fn upload(&self, outcome: &SummarizeOutcome, names: &NameConfig) -> Result<(), PipelineError> {
+        self.storage.upload_arxiv_as_jsonl(
+            &names.bucket,
+            &names.jsonl_path(),
+            &outcome.results).await?;
+        // the failures sidecar is only written when some papers failed, one
+        // left by an earlier run of the same day is removed.
+        if outcome.failures.is_empty() {
+            self.storage.delete(&names.bucket, &names.failures_path()).await?;
+        } else {
+            self.storage.upload_failures_as_jsonl(
+                &names.bucket,
+                &names.failures_path(),
+                &outcome.failures).await?;
+        }
+        println!("uploaded {} summaries, {} failures to {}",
+            outcome.results.len(), outcome.failures.len(), names.jsonl_path());
+        Ok(())
+    }
+
+    /// Single day run, written to that day's outputs.
+    pub async fn run_day(&self, day: &DateRange) -> Result<(), PipelineError> {
+        self.run(day, &self.names.for_date(day.from.date_naive())).await
+    }
+
+    /// Runs every day of `range` on its own, skipping days whose output
+    /// already exists in storage.
+    pub async fn backfill(&self, range: &DateRange) -> Result<(), PipelineError> {
+        for day in range.split_days() {
+            let names = self.names.for_date(day.from.date_naive());
+            if self.storage.exists(&names.bucket, &names.jsonl_path()).await? {
+                println!("skipping {}, {} exists", day, names.jsonl_path());
+                continue;
+            }
+            self.run(&day, &names).await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct PipelineError {
+    pub message: String
+}
+
+impl PipelineError {
+    pub fn new(message: &str) -> Self {
+        Self {
+            message: message.to_string()
+        }
+    }
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl StdError for PipelineError {}
+
+impl From<ParserError> for PipelineError {
+    fn from(err: ParserError) -> Self {
+        PipelineError::new(&format!("Parser error: {}", err))
+    }
+}
+
+impl From<StorageError> for PipelineError {
+    fn from(err: StorageError) -> Self {
+        PipelineError::new(&format!("Storage error: {}", err))
+    }
+}
@@ -1,5 +1,24 @@
+use std::{
+    error::Error as StdError,
+    fmt,
+    sync::LazyLock
+};
+use regex::{Captures, Regex};
+
+use crate::{hash, model::ArxivResult};
+
+// Prompt templates. The user message is rendered from the paper with
+// `{placeholder}`s; templates are named and versioned so that every summary
+// records the prompt that produced it, e.g. `summary-v2`.
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{(\w+)\}").unwrap());
+
+const PLACEHOLDERS: &[&str] = &["title", "authors", "categories", "comment", "abstract"];
+
 // TODO: improve the prompt to yield better, more concise responses.
-pub const PROMPT: &str = 
+// `summary-v1`, kept exactly as summaries before versioning were made with it,
+// typo included.
+pub const PROMPT: &str =
     "Act as a research assistant that specializes in machine learning. \
     You are supposed to analyze and summarize research paper abstracts \
     to post on a research community newsletter. When teh user provides you \
@@ -8,3 +27,155 @@ pub const PROMPT: &str =
     tackles, some of the technical details related to the research, \
     and the key resulting findings of the research. Remember, the summary \
     must be concise.";
+
+const PROMPT_WITH_CONTEXT: &str =
+    "Act as a research assistant that specializes in machine learning. \
+    You are supposed to analyze and summarize research papers to post on a \
+    research community newsletter. The user provides a paper's title, authors, \
+    categories, comments and abstract; write a three sentence summary of the \
+    paper. The summary must be concise, focused on what new things the \
+    research tackles, some of the technical details related to the research, \
+    and the key resulting findings of the research. Use the other fields only \
+    as context, do not repeat the title or list the authors.";
+
+const USER_WITH_CONTEXT: &str =
+    "Title: {title}\nAuthors: {authors}\nCategories: {categories}\nComments: {comment}\n\n{abstract}";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub version: u32,
+    pub system: String,
+    // user message with placeholders.
+    pub user: String,
+    // short hash of the text, set for prompts of your own.
+    pub digest: Option<String>
+}
+
+impl PromptTemplate {
+    /// Template with placeholders among `{title}`, `{authors}`,
+    /// `{categories}`, `{comment}` and `{abstract}` in `user`.
+    pub fn new(name: &str, version: u32, system: &str, user: &str) -> Result<Self, PromptError> {
+        if let Some(unknown) = PLACEHOLDER.captures_iter(user)
+            .map(|caps| caps[1].to_string())
+            .find(|name| !PLACEHOLDERS.contains(&name.as_str())) {
+            return Err(PromptError::new(&format!(
+                "unknown placeholder {{{}}} in prompt {}, expected one of {}",
+                unknown, name, PLACEHOLDERS.join(", "))));
+        }
+        Ok(PromptTemplate {
+            name: name.to_string(),
+            version,
+            system: system.to_string(),
+            user: user.to_string(),
+            digest: None
+        })
+    }
+
+    /// Same template with `digest` set from its text, so that editing a
+    /// prompt changes its id even when the version is not bumped.
+    pub fn with_digest(self) -> Self {
+        let hash = hash::fnv1a(self.system.bytes().chain([0]).chain(self.user.bytes()));
+        PromptTemplate { digest: Some(format!("{:016x}", hash)), ..self }
+    }
+
+    /// Built-in prompt sets by id, e.g. `summary-v1`.
+    pub fn builtin(id: &str) -> Option<Self> {
+        let template = match id {
+            // the abstract on its own.
+            "summary-v1" => Self::new("summary", 1, PROMPT, "{abstract}"),
+            // the abstract with the listing's metadata as context.
+            "summary-v2" => Self::new("summary", 2, PROMPT_WITH_CONTEXT, USER_WITH_CONTEXT),
+            _ => return None
+        };
+        template.ok()
+    }
+
+    // `{name}-v{version}`, followed by `-{digest}` when set, recorded with
+    // every summary.
+    pub fn id(&self) -> String {
+        match &self.digest {
+            Some(digest) => format!("{}-v{}-{}", self.name, self.version, digest),
+            None => format!("{}-v{}", self.name, self.version)
+        }
+    }
+
+    /// User message for `data`, with LaTeX converted to Unicode.
+    pub fn render(&self, data: &ArxivResult) -> String {
+        PLACEHOLDER.replace_all(&self.user, |caps: &Captures| match &caps[1] {
+            "title" => data.clean_title(),
+            "authors" => data.authors.iter()
+                .map(|author| author.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            "categories" if data.categories.is_empty() => data.primary_category.clone(),
+            "categories" => data.categories.join(", "),
+            "comment" => data.comment.clone().unwrap_or_else(|| String::from("none")),
+            "abstract" => data.clean_summary(),
+            _ => caps[0].to_string()
+        }).to_string()
+    }
+}
+
+impl Default for PromptTemplate {
+    fn default() -> Self {
+        Self::builtin("summary-v1").unwrap()
+    }
+}
+
+#[derive(Debug)]
+pub struct PromptError {
+    pub message: String
+}
+
+impl PromptError {
+    pub fn new(message: &str) -> Self {
+        Self {
+            message: message.to_string()
+        }
+    }
+}
+
+impl fmt::Display for PromptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl StdError for PromptError {}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::model::Author;
+
+    #[test]
+    fn test_render() {
+        let mut data = ArxivResult::new(
+            "2501.01234".to_string(),
+            r#"Sch\"{o}dinger Bridges"#.to_string(),
+            "An  abstract.".to_string(),
+            vec![
+                Author::new("Jane Doe".to_string(), vec![]),
+                Author::new("John Roe".to_string(), vec![])
+            ],
+            Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap(),
+            "http://arxiv.org/abs/2501.01234v1".to_string());
+        data.categories = vec!["cs.LG".to_string(), "stat.ML".to_string()];
+
+        let template = PromptTemplate::builtin("summary-v2").unwrap();
+        assert_eq!(template.id(), "summary-v2");
+        assert_eq!(template.render(&data), "Title: Schödinger Bridges\nAuthors: Jane Doe, John Roe\n\
+            Categories: cs.LG, stat.ML\nComments: none\n\nAn abstract.");
+        assert_eq!(PromptTemplate::default().render(&data), "An abstract.");
+    }
+
+    #[test]
+    fn test_unknown_placeholder() {
+        let err = PromptTemplate::new("custom", 1, "system", "{title}: {abstrct}").unwrap_err();
+        assert!(err.message.contains("{abstrct}"));
+        assert!(PromptTemplate::builtin("summary-v9").is_none());
+    }
+}
@@ -0,0 +1,493 @@
+use std::{
+    sync::LazyLock,
+    time::Duration
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, NaiveTime, Utc};
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::{
+    config::PubmedConfig,
+    http::HttpClient,
+    model::{ArxivResult, Author, DateRange, Source},
+    parser::ParserError,
+    source::{clean, previous_day, require_text, skip_malformed, InlineMarkup, PaperSource}
+};
+
+// PubMed through the NCBI E-utilities: `esearch` lists the PMIDs matching the
+// search term, `efetch` returns their records. See
+// https://www.ncbi.nlm.nih.gov/books/NBK25501/
+//
+// Records are dated by the day they entered PubMed (the Entrez date).
+
+const EUTILS_URL: &str = "https://eutils.ncbi.nlm.nih.gov/entrez/eutils";
+// NCBI allows 3 requests per second, 10 with an API key.
+const REQUEST_INTERVAL: Duration = Duration::from_millis(340);
+const KEYED_REQUEST_INTERVAL: Duration = Duration::from_millis(100);
+// PMIDs per `esearch` page and `efetch` request.
+const PAGE_SIZE: usize = 200;
+
+// elements with inline markup such as `<i>` or `<sup>`.
+static MARKUP: LazyLock<InlineMarkup> = LazyLock::new(|| InlineMarkup::new(&["ArticleTitle", "AbstractText"]));
+
+#[derive(Debug)]
+pub struct Pubmed {
+    config: PubmedConfig,
+    client: HttpClient
+}
+
+impl Pubmed {
+    pub fn from_config(config: PubmedConfig) -> Self {
+        let interval = match config.api_key {
+            Some(_) => KEYED_REQUEST_INTERVAL,
+            None => REQUEST_INTERVAL
+        };
+        Pubmed {
+            client: HttpClient::new(interval, config.retry.clone()),
+            config
+        }
+    }
+
+    fn eutils_url(&self, tool: &str, params: &[(&str, &str)]) -> String {
+        let mut params = params.to_vec();
+        if let Some(api_key) = &self.config.api_key {
+            params.push(("api_key", api_key));
+        }
+        let base = format!("{}/{}.fcgi", EUTILS_URL, tool);
+        Url::parse_with_params(&base, &params)
+            .map(|url| url.to_string())
+            .unwrap_or(base)
+    }
+
+    fn search_url(&self, range: &DateRange, start: usize) -> String {
+        // both ends are inclusive days.
+        let from = range.from.format("%Y/%m/%d").to_string();
+        let until = (range.to - ChronoDuration::seconds(1)).format("%Y/%m/%d").to_string();
+        self.eutils_url("esearch", &[
+            ("db", "pubmed"),
+            ("term", &self.config.term),
+            ("datetype", "edat"),
+            ("mindate", &from),
+            ("maxdate", &until),
+            ("retstart", &start.to_string()),
+            ("retmax", &PAGE_SIZE.to_string()),
+            ("retmode", "json")
+        ])
+    }
+
+    async fn search(&self, range: &DateRange) -> Result<Vec<String>, ParserError> {
+        let mut pmids: Vec<String> = Vec::new();
+        loop {
+            let url = self.search_url(range, pmids.len());
+            println!("using pubmed url: {}", url);
+            let response: SearchResponse = serde_json::from_str(&self.client.get(&url).await?)?;
+            let result = response.esearchresult;
+            if let Some(error) = result.error {
+                return Err(ParserError::Api(error));
+            }
+            let total: usize = result.count.trim().parse().unwrap_or(0);
+            if result.idlist.is_empty() {
+                break;
+            }
+            pmids.extend(result.idlist);
+            if pmids.len() >= total {
+                break;
+            }
+        }
+        Ok(pmids)
+    }
+
+    async fn fetch_records<S: AsRef<str>>(&self, pmids: &[S]) -> Result<Vec<ArxivResult>, ParserError> {
+        let mut results: Vec<ArxivResult> = Vec::new();
+        for batch in pmids.chunks(PAGE_SIZE) {
+            let ids = batch.iter()
+                .map(|pmid| pmid.as_ref().trim())
+                .collect::<Vec<_>>()
+                .join(",");
+            let url = self.eutils_url("efetch", &[
+                ("db", "pubmed"),
+                ("id", &ids),
+                ("retmode", "xml")
+            ]);
+            let xml = self.client.get(&url).await?;
+            let parsed: ArticleSet = quick_xml::de::from_str(&MARKUP.strip(&xml))?;
+            for article in parsed.articles {
+                // records without a history cannot be placed in a day, many
+                // have no abstract.
+                let result = article.into_result(results.len()).and_then(require_text);
+                if let Some(result) = skip_malformed(result)? {
+                    results.push(result);
+                }
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[async_trait]
+impl PaperSource for Pubmed {
+    fn source(&self) -> Source {
+        Source::Pubmed
+    }
+
+    fn default_range(&self) -> DateRange {
+        previous_day()
+    }
+
+    async fn fetch(&self, range: &DateRange) -> Result<Vec<ArxivResult>, ParserError> {
+        let pmids = self.search(range).await?;
+        println!("pmids {}", pmids.len());
+        self.fetch_records(&pmids).await
+    }
+
+    /// Articles by PMID.
+    async fn fetch_by_ids(&self, ids: &[&str]) -> Result<Vec<ArxivResult>, ParserError> {
+        self.fetch_records(ids).await
+    }
+}
+
+impl PubmedArticle {
+    fn into_result(self, index: usize) -> Result<ArxivResult, ParserError> {
+        let citation = self.citation;
+        let article = citation.article;
+        let pmid = citation.pmid.value.trim().to_string();
+
+        let entrez = self.data.history.dates.iter()
+            .find(|date| date.status == "entrez")
+            .or_else(|| self.data.history.dates.iter().find(|date| date.status == "pubmed"));
+        let published = match entrez {
+            Some(date) => date.to_datetime()?,
+            None => return Err(ParserError::MalformedEntry(format!("{} has no entrez or pubmed date", pmid)))
+        };
+
+        // structured abstracts are split into labelled sections.
+        let summary = article.summary.sections.iter()
+            .map(|section| match &section.label {
+                Some(label) => format!("{}: {}", label, clean(&section.value)),
+                None => clean(&section.value)
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let doi = self.data.ids.ids.iter()
+            .find(|id| id.id_type == "doi")
+            .or_else(|| article.locations.iter().find(|id| id.id_type == "doi"))
+            .map(|id| clean(&id.value));
+        let journal = clean(&article.journal.title);
+
+        Ok(ArxivResult {
+            source: Source::Pubmed,
+            link: format!("https://pubmed.ncbi.nlm.nih.gov/{}/", pmid),
+            id: pmid,
+            version: 1,
+            index,
+            title: clean(&article.title),
+            summary,
+            authors: article.authors.authors.iter()
+                .map(|author| {
+                    let name = match &author.collective_name {
+                        Some(name) => clean(name),
+                        None => clean(&format!("{} {}",
+                            author.fore_name.as_deref().unwrap_or_default(), author.last_name))
+                    };
+                    Author::new(name, author.affiliations.iter()
+                        .map(|info| clean(&info.affiliation))
+                        .collect())
+                })
+                .collect(),
+            published,
+            updated: published,
+            primary_category: journal.clone(),
+            categories: citation.mesh.headings.iter()
+                .map(|heading| clean(&heading.descriptor.value))
+                .collect(),
+            comment: None,
+            journal_ref: Some(journal).filter(|journal| !journal.is_empty()),
+            doi,
+            announce_type: None
+        })
+    }
+}
+
+impl HistoryDate {
+    fn to_datetime(&self) -> Result<DateTime<Utc>, ParserError> {
+        let value = format!("{}-{}-{}", self.year.trim(), self.month.trim(), self.day.trim());
+        NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+            .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+            .map_err(|e| ParserError::Date { value, source: e })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SearchResponse {
+    esearchresult: SearchResult
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SearchResult {
+    count: String,
+    idlist: Vec<String>,
+    #[serde(rename = "ERROR")]
+    error: Option<String>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ArticleSet {
+    #[serde(rename = "PubmedArticle")]
+    articles: Vec<PubmedArticle>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PubmedArticle {
+    #[serde(rename = "MedlineCitation")]
+    citation: MedlineCitation,
+    #[serde(rename = "PubmedData")]
+    data: PubmedData
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MedlineCitation {
+    #[serde(rename = "PMID")]
+    pmid: TextField,
+    #[serde(rename = "Article")]
+    article: Article,
+    #[serde(rename = "MeshHeadingList")]
+    mesh: MeshHeadingList
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TextField {
+    #[serde(rename = "$text")]
+    value: String
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Article {
+    #[serde(rename = "Journal")]
+    journal: Journal,
+    #[serde(rename = "ArticleTitle")]
+    title: String,
+    #[serde(rename = "ELocationID")]
+    locations: Vec<ArticleId>,
+    #[serde(rename = "Abstract")]
+    summary: AbstractField,
+    #[serde(rename = "AuthorList")]
+    authors: AuthorList
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Journal {
+    #[serde(rename = "Title")]
+    title: String
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AbstractField {
+    #[serde(rename = "AbstractText")]
+    sections: Vec<AbstractText>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AbstractText {
+    #[serde(rename = "@Label")]
+    label: Option<String>,
+    #[serde(rename = "$text")]
+    value: String
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AuthorList {
+    #[serde(rename = "Author")]
+    authors: Vec<PubmedAuthor>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PubmedAuthor {
+    #[serde(rename = "LastName")]
+    last_name: String,
+    #[serde(rename = "ForeName")]
+    fore_name: Option<String>,
+    #[serde(rename = "CollectiveName")]
+    collective_name: Option<String>,
+    #[serde(rename = "AffiliationInfo")]
+    affiliations: Vec<AffiliationInfo>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AffiliationInfo {
+    #[serde(rename = "Affiliation")]
+    affiliation: String
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MeshHeadingList {
+    #[serde(rename = "MeshHeading")]
+    headings: Vec<MeshHeading>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MeshHeading {
+    #[serde(rename = "DescriptorName")]
+    descriptor: TextField
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PubmedData {
+    #[serde(rename = "History")]
+    history: History,
+    #[serde(rename = "ArticleIdList")]
+    ids: ArticleIdList
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct History {
+    #[serde(rename = "PubMedPubDate")]
+    dates: Vec<HistoryDate>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct HistoryDate {
+    #[serde(rename = "@PubStatus")]
+    status: String,
+    #[serde(rename = "Year")]
+    year: String,
+    #[serde(rename = "Month")]
+    month: String,
+    #[serde(rename = "Day")]
+    day: String
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ArticleIdList {
+    #[serde(rename = "ArticleId")]
+    ids: Vec<ArticleId>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ArticleId {
+    #[serde(rename = "@IdType", alias = "@EIdType")]
+    id_type: String,
+    #[serde(rename = "$text")]
+    value: String
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const ARTICLES: &str = r#"<?xml version="1.0" ?>
+    <!DOCTYPE PubmedArticleSet PUBLIC "-//NLM//DTD PubMedArticle, 1st January 2025//EN" "https://dtd.nlm.nih.gov/ncbi/pubmed/out/pubmed_250101.dtd">
+    <PubmedArticleSet>
+      <PubmedArticle>
+        <MedlineCitation Status="PubMed-not-MEDLINE" Owner="NLM">
+          <PMID Version="1">39700001</PMID>
+          <Article PubModel="Print-Electronic">
+            <Journal>
+              <ISSN IssnType="Electronic">1476-4687</ISSN>
+              <Title>Nature</Title>
+              <ISOAbbreviation>Nature</ISOAbbreviation>
+            </Journal>
+            <ArticleTitle>Language models for <i>in vivo</i> studies.</ArticleTitle>
+            <ELocationID EIdType="pii" ValidYN="Y">s41586-025-0001</ELocationID>
+            <ELocationID EIdType="doi" ValidYN="Y">10.1038/s41586-025-0001</ELocationID>
+            <Abstract>
+              <AbstractText Label="BACKGROUND" NlmCategory="BACKGROUND">Some   background.</AbstractText>
+              <AbstractText Label="RESULTS" NlmCategory="RESULTS">We found x<sup>2</sup>.</AbstractText>
+            </Abstract>
+            <AuthorList CompleteYN="Y">
+              <Author ValidYN="Y">
+                <LastName>Doe</LastName>
+                <ForeName>Jane</ForeName>
+                <Initials>J</Initials>
+                <AffiliationInfo><Affiliation>MIT, Cambridge, USA.</Affiliation></AffiliationInfo>
+              </Author>
+              <Author ValidYN="Y">
+                <CollectiveName>The Consortium</CollectiveName>
+              </Author>
+            </AuthorList>
+            <Language>eng</Language>
+          </Article>
+          <MeshHeadingList>
+            <MeshHeading><DescriptorName UI="D006801" MajorTopicYN="N">Humans</DescriptorName></MeshHeading>
+            <MeshHeading><DescriptorName UI="D009323" MajorTopicYN="Y">Natural Language Processing</DescriptorName></MeshHeading>
+          </MeshHeadingList>
+        </MedlineCitation>
+        <PubmedData>
+          <History>
+            <PubMedPubDate PubStatus="received"><Year>2024</Year><Month>6</Month><Day>1</Day></PubMedPubDate>
+            <PubMedPubDate PubStatus="entrez"><Year>2025</Year><Month>1</Month><Day>3</Day><Hour>6</Hour><Minute>0</Minute></PubMedPubDate>
+          </History>
+          <PublicationStatus>aheadofprint</PublicationStatus>
+          <ArticleIdList>
+            <ArticleId IdType="pubmed">39700001</ArticleId>
+            <ArticleId IdType="doi">10.1038/s41586-025-0001</ArticleId>
+          </ArticleIdList>
+        </PubmedData>
+      </PubmedArticle>
+    </PubmedArticleSet>"#;
+
+    #[test]
+    fn test_article_parsing() {
+        let xml = MARKUP.strip(ARTICLES);
+        let parsed: ArticleSet = quick_xml::de::from_str(&xml).unwrap();
+        let result = parsed.articles.into_iter().next().unwrap().into_result(0).unwrap();
+        assert_eq!(result.source, Source::Pubmed);
+        assert_eq!(result.id, "39700001");
+        assert_eq!(result.title, "Language models for in vivo studies.");
+        assert_eq!(result.summary, "BACKGROUND: Some background. RESULTS: We found x2.");
+        assert_eq!(result.authors, vec![
+            Author::new("Jane Doe".to_string(), vec!["MIT, Cambridge, USA.".to_string()]),
+            Author::new("The Consortium".to_string(), vec![])
+        ]);
+        assert_eq!(result.published, Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).unwrap());
+        assert_eq!(result.link, "https://pubmed.ncbi.nlm.nih.gov/39700001/");
+        assert_eq!(result.journal_ref.as_deref(), Some("Nature"));
+        assert_eq!(result.categories, vec!["Humans", "Natural Language Processing"]);
+        assert_eq!(result.doi.as_deref(), Some("10.1038/s41586-025-0001"));
+
+        let undated = r#"<PubmedArticleSet><PubmedArticle>
+            <MedlineCitation><PMID>39700002</PMID></MedlineCitation>
+        </PubmedArticle></PubmedArticleSet>"#;
+        let parsed: ArticleSet = quick_xml::de::from_str(undated).unwrap();
+        assert!(matches!(
+            parsed.articles.into_iter().next().unwrap().into_result(0),
+            Err(ParserError::MalformedEntry(_))
+        ));
+    }
+
+    #[test]
+    fn test_search_url() {
+        let pubmed = Pubmed::from_config(PubmedConfig::new("\"language model\"[tiab]"));
+        let range = DateRange::days(
+            NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 1, 3).unwrap()
+        );
+        assert_eq!(pubmed.search_url(&range, 200), concat!(
+            "https://eutils.ncbi.nlm.nih.gov/entrez/eutils/esearch.fcgi?db=pubmed",
+            "&term=%22language+model%22%5Btiab%5D&datetype=edat&mindate=2025%2F01%2F02",
+            "&maxdate=2025%2F01%2F02&retstart=200&retmax=200&retmode=json"
+        ));
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt;
+use chrono::Duration;
+
+use crate::model::DateRange;
+
+// Typed builder for arXiv API queries. See
+// https://info.arxiv.org/help/api/user-manual.html#query_details
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Title,
+    Author,
+    Abstract,
+    Comment,
+    JournalRef,
+    Category,
+    ReportNumber,
+    Id,
+    All
+}
+
+impl Field {
+    fn prefix(&self) -> &'static str {
+        match self {
+            Field::Title => "ti",
+            Field::Author => "au",
+            Field::Abstract => "abs",
+            Field::Comment => "co",
+            Field::JournalRef => "jr",
+            Field::Category => "cat",
+            Field::ReportNumber => "rn",
+            Field::Id => "id",
+            Field::All => "all"
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchQuery {
+    Term(Field, String),
+    // `submittedDate:[from TO to]`, formatted as `YYYYMMDDHHMM`. Both ends
+    // are inclusive.
+    SubmittedDate(String, String),
+    // search syntax of its own, operators included. Its terms are kept as
+    // written, only URL-encoded with the field colons left in place, and the
+    // whole query is grouped when combined with others.
+    Raw(String),
+    And(Box<SearchQuery>, Box<SearchQuery>),
+    Or(Box<SearchQuery>, Box<SearchQuery>),
+    AndNot(Box<SearchQuery>, Box<SearchQuery>)
+}
+
+impl SearchQuery {
+    pub fn term(field: Field, value: &str) -> Self {
+        SearchQuery::Term(field, value.to_string())
+    }
+
+    pub fn title(value: &str) -> Self {
+        Self::term(Field::Title, value)
+    }
+
+    pub fn author(value: &str) -> Self {
+        Self::term(Field::Author, value)
+    }
+
+    pub fn abs(value: &str) -> Self {
+        Self::term(Field::Abstract, value)
+    }
+
+    pub fn comment(value: &str) -> Self {
+        Self::term(Field::Comment, value)
+    }
+
+    pub fn category(value: &str) -> Self {
+        Self::term(Field::Category, value)
+    }
+
+    pub fn all(value: &str) -> Self {
+        Self::term(Field::All, value)
+    }
+
+    pub fn raw(query: &str) -> Self {
+        SearchQuery::Raw(query.to_string())
+    }
+
+    /// Papers submitted in `range`. arXiv ranges are inclusive at minute
+    /// granularity, so the end is moved back by a minute to keep adjacent
+    /// ranges from overlapping.
+    pub fn submitted_in(range: &DateRange) -> Self {
+        SearchQuery::SubmittedDate(
+            range.from.format("%Y%m%d%H%M").to_string(),
+            (range.to - Duration::minutes(1)).format("%Y%m%d%H%M").to_string()
+        )
+    }
+
+    pub fn and(self, other: SearchQuery) -> Self {
+        SearchQuery::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: SearchQuery) -> Self {
+        SearchQuery::Or(Box::new(self), Box::new(other))
+    }
+
+    pub fn and_not(self, other: SearchQuery) -> Self {
+        SearchQuery::AndNot(Box::new(self), Box::new(other))
+    }
+
+    /// ORs all queries together, `None` if there are none.
+    pub fn any_of<I: IntoIterator<Item = SearchQuery>>(queries: I) -> Option<Self> {
+        queries.into_iter().reduce(SearchQuery::or)
+    }
+
+    /// URL encoded value of the `search_query` parameter.
+    pub fn encode(&self) -> String {
+        match self {
+            SearchQuery::Term(field, value) => {
+                let value = value.trim();
+                // multi-word values are searched as a phrase.
+                if value.contains(char::is_whitespace) {
+                    format!("{}:%22{}%22", field.prefix(), encode_value(value))
+                } else {
+                    format!("{}:{}", field.prefix(), encode_value(value))
+                }
+            },
+            SearchQuery::SubmittedDate(from, to) => {
+                format!("submittedDate:[{}+TO+{}]", from, to)
+            },
+            SearchQuery::Raw(query) => encode_raw(query),
+            SearchQuery::And(lhs, rhs) => self.encode_binary("AND", lhs, rhs),
+            SearchQuery::Or(lhs, rhs) => self.encode_binary("OR", lhs, rhs),
+            SearchQuery::AndNot(lhs, rhs) => self.encode_binary("ANDNOT", lhs, rhs)
+        }
+    }
+
+    fn encode_binary(&self, op: &str, lhs: &SearchQuery, rhs: &SearchQuery) -> String {
+        // a chain of the same operator is left flat, anything else is grouped.
+        let lhs = if lhs.needs_group() && !lhs.same_op(self) {
+            format!("%28{}%29", lhs.encode())
+        } else {
+            lhs.encode()
+        };
+        let rhs = if rhs.needs_group() {
+            format!("%28{}%29", rhs.encode())
+        } else {
+            rhs.encode()
+        };
+        format!("{}+{}+{}", lhs, op, rhs)
+    }
+
+    // raw queries may hold operators of their own, so they are grouped like
+    // binary ones.
+    fn needs_group(&self) -> bool {
+        matches!(self,
+            SearchQuery::Raw(..) | SearchQuery::And(..) | SearchQuery::Or(..) | SearchQuery::AndNot(..))
+    }
+
+    fn same_op(&self, other: &SearchQuery) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+fn encode_value(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            // `/` is valid in query strings and part of old-style ids.
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'*' | b'/' => {
+                encoded.push(byte as char)
+            },
+            b' ' | b'\t' | b'\n' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte))
+        }
+    }
+    encoded
+}
+
+fn encode_raw(query: &str) -> String {
+    query.split_whitespace()
+        .map(|word| word.split(':')
+            .map(encode_value)
+            .collect::<Vec<_>>()
+            .join(":"))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    Relevance,
+    LastUpdatedDate,
+    SubmittedDate
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending
+}
+
+impl fmt::Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SortBy::Relevance => "relevance",
+            SortBy::LastUpdatedDate => "lastUpdatedDate",
+            SortBy::SubmittedDate => "submittedDate"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SortOrder::Ascending => "ascending",
+            SortOrder::Descending => "descending"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArxivQuery {
+    search: Option<SearchQuery>,
+    id_list: Vec<String>,
+    start: usize,
+    max_results: Option<usize>,
+    sort_by: Option<SortBy>,
+    sort_order: Option<SortOrder>
+}
+
+impl ArxivQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn search(mut self, query: SearchQuery) -> Self {
+        self.search = Some(query);
+        self
+    }
+
+    pub fn id_list<S: AsRef<str>>(mut self, ids: &[S]) -> Self {
+        self.id_list = ids.iter().map(|id| id.as_ref().to_string()).collect();
+        self
+    }
+
+    pub fn start(mut self, start: usize) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub fn max_results(mut self, max_results: usize) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    pub fn sort_by(mut self, sort_by: SortBy) -> Self {
+        self.sort_by = Some(sort_by);
+        self
+    }
+
+    pub fn sort_order(mut self, sort_order: SortOrder) -> Self {
+        self.sort_order = Some(sort_order);
+        self
+    }
+
+    pub fn to_url(&self, base: &str) -> String {
+        let mut params: Vec<String> = Vec::new();
+        if let Some(search) = &self.search {
+            params.push(format!("search_query={}", search.encode()));
+        }
+        if !self.id_list.is_empty() {
+            let ids = self.id_list.iter()
+                .map(|id| encode_value(id.trim()))
+                .collect::<Vec<_>>();
+            params.push(format!("id_list={}", ids.join(",")));
+        }
+        params.push(format!("start={}", self.start));
+        if let Some(max_results) = self.max_results {
+            params.push(format!("max_results={}", max_results));
+        }
+        if let Some(sort_by) = self.sort_by {
+            params.push(format!("sortBy={}", sort_by));
+        }
+        if let Some(sort_order) = self.sort_order {
+            params.push(format!("sortOrder={}", sort_order));
+        }
+        format!("{}?{}", base, params.join("&"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_query() {
+        let query = SearchQuery::category("cs.CL")
+            .and(SearchQuery::abs("retrieval augmented"))
+            .and_not(SearchQuery::title("survey"));
+        assert_eq!(
+            query.encode(),
+            "%28cat:cs.CL+AND+abs:%22retrieval+augmented%22%29+ANDNOT+ti:survey"
+        );
+    }
+
+    #[test]
+    fn test_grouping() {
+        let categories = SearchQuery::any_of(vec![
+            SearchQuery::category("cs.CL"),
+            SearchQuery::category("cs.AI")
+        ]).unwrap();
+        let query = SearchQuery::author("del maestro")
+            .and(categories)
+            .and(SearchQuery::comment("ACL 2025"));
+        assert_eq!(
+            query.encode(),
+            "au:%22del+maestro%22+AND+%28cat:cs.CL+OR+cat:cs.AI%29+AND+co:%22ACL+2025%22"
+        );
+        assert_eq!(
+            SearchQuery::raw("abs:\"large language\" ANDNOT ti:survey").encode(),
+            "abs:%22large+language%22+ANDNOT+ti:survey"
+        );
+    }
+
+    #[test]
+    fn test_raw_operand() {
+        let query = SearchQuery::category("cs.CL")
+            .and(SearchQuery::raw("abs:a OR abs:b"))
+            .and(SearchQuery::SubmittedDate("202501010000".to_string(), "202501012359".to_string()));
+        assert_eq!(
+            query.encode(),
+            "cat:cs.CL+AND+%28abs:a+OR+abs:b%29+AND+submittedDate:[202501010000+TO+202501012359]"
+        );
+    }
+
+    #[test]
+    fn test_query_url() {
+        let url = ArxivQuery::new()
+            .search(SearchQuery::all("electron"))
+            .start(100)
+            .max_results(50)
+            .sort_by(SortBy::SubmittedDate)
+            .sort_order(SortOrder::Descending)
+            .to_url("https://export.arxiv.org/api/query");
+        assert_eq!(url, concat!(
+            "https://export.arxiv.org/api/query?search_query=all:electron",
+            "&start=100&max_results=50&sortBy=submittedDate&sortOrder=descending"
+        ));
+
+        let url = ArxivQuery::new()
+            .id_list(&["2501.01234", "hep-th/9901001v1"])
+            .to_url("https://export.arxiv.org/api/query");
+        assert_eq!(url, "https://export.arxiv.org/api/query?id_list=2501.01234,hep-th/9901001v1&start=0");
+    }
+}
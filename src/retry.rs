@@ -0,0 +1,46 @@
+use std::time::Duration;
+use rand::Rng;
+
+use crate::config::RetryConfig;
+
+// Exponential backoff with full jitter: the n-th retry waits a random
+// duration in [0, min(max_delay, base_delay * 2^(n-1))]. A server provided
+// retry-after takes precedence, capped at `max_delay`.
+pub fn backoff(config: &RetryConfig, attempt: u32, retry_after: Option<Duration>) -> Duration {
+    if let Some(retry_after) = retry_after {
+        return retry_after.min(config.max_delay);
+    }
+    let exp = 2_u32.saturating_pow(attempt.saturating_sub(1));
+    let ceiling = config.base_delay
+        .saturating_mul(exp)
+        .min(config.max_delay);
+    if ceiling.is_zero() {
+        return ceiling;
+    }
+    rand::thread_rng().gen_range(Duration::ZERO..=ceiling)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_bounds() {
+        let config = RetryConfig::new(5, Duration::from_secs(1), Duration::from_secs(10));
+        for attempt in 1..=8 {
+            let ceiling = Duration::from_secs(1 << (attempt - 1)).min(config.max_delay);
+            for _ in 0..100 {
+                assert!(backoff(&config, attempt, None) <= ceiling);
+            }
+        }
+    }
+
+    #[test]
+    fn test_backoff_retry_after() {
+        let config = RetryConfig::new(5, Duration::from_secs(1), Duration::from_secs(10));
+        let delay = backoff(&config, 1, Some(Duration::from_secs(7)));
+        assert_eq!(delay, Duration::from_secs(7));
+        let delay = backoff(&config, 1, Some(Duration::from_secs(60)));
+        assert_eq!(delay, config.max_delay);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::Deserialize;
+
+use crate::{
+    config::ArxivConfig,
+    model::{split_arxiv_id, split_authors, AnnounceType, ArxivResult, DateRange, Source},
+    http::HttpClient,
+    parser::ParserError,
+    source::clean
+};
+
+// Daily announcement feeds, e.g. https://rss.arxiv.org/rss/cs.CL. See
+// https://info.arxiv.org/help/rss.html
+//
+// A feed only holds the latest announcement: the papers that appeared in
+// today's listing, dated with the announcement and not the submission. It is
+// empty on days without an announcement.
+
+const RSS_URL: &str = "https://rss.arxiv.org/rss/";
+
+#[derive(Debug)]
+pub struct RssFeed {
+    config: ArxivConfig,
+    client: HttpClient
+}
+
+impl Default for RssFeed {
+    fn default() -> Self {
+        Self::from_config(ArxivConfig::default())
+    }
+}
+
+impl RssFeed {
+    pub fn from_config(config: ArxivConfig) -> Self {
+        RssFeed {
+            client: HttpClient::new(config.request_interval, config.retry.clone()),
+            config
+        }
+    }
+
+    /// Today, in UTC. Announcements are dated at midnight US Eastern, which
+    /// falls on the same UTC day.
+    pub fn default_range(&self) -> DateRange {
+        DateRange::day(Utc::now().date_naive())
+    }
+
+    // categories are combined into one feed, e.g. `cs.CL+cs.AI`.
+    fn feed_url(&self) -> String {
+        format!("{}{}", RSS_URL, self.config.categories.join("+"))
+    }
+
+    /// Papers of the current announcement, which has to fall within `range`.
+    pub async fn get_arxiv_results(
+        &self,
+        range: &DateRange
+    ) -> Result<Vec<ArxivResult>, ParserError> {
+        let url = self.feed_url();
+        println!("using feed url: {}", url);
+        let xml = self.client.get(&url).await?;
+        let parsed: RssDocument = quick_xml::de::from_str(&xml)?;
+        let results = parse_items(parsed.channel.items)?;
+        println!("announced {}", results.len());
+
+        // the feed cannot be rewound, refuse instead of returning an empty day.
+        if let Some(announced) = results.first().map(|result| result.published) {
+            if announced < range.from || announced >= range.to {
+                return Err(ParserError::Unavailable(format!(
+                    "feed holds the announcement of {}, not {}",
+                    announced.format("%Y-%m-%d"), range)));
+            }
+        }
+        Ok(results)
+    }
+}
+
+fn parse_items(items: Vec<RssItem>) -> Result<Vec<ArxivResult>, ParserError> {
+    let mut results: Vec<ArxivResult> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for item in items {
+        let result = item.into_result(results.len())?;
+        match positions.get(&result.id) {
+            // a paper cross-listed between the requested categories is new to
+            // the combined listing.
+            Some(&position) => {
+                let existing = &mut results[position];
+                existing.announce_type = match (existing.announce_type, result.announce_type) {
+                    (Some(AnnounceType::Cross), Some(AnnounceType::New)) => Some(AnnounceType::New),
+                    (Some(AnnounceType::ReplaceCross), Some(AnnounceType::Replace)) => {
+                        Some(AnnounceType::Replace)
+                    },
+                    (announce_type, _) => announce_type
+                };
+            },
+            None => {
+                positions.insert(result.id.clone(), results.len());
+                results.push(result);
+            }
+        }
+    }
+    Ok(results)
+}
+
+impl RssItem {
+    fn into_result(self, index: usize) -> Result<ArxivResult, ParserError> {
+        // `oai:arXiv.org:2501.01234v1`
+        let guid = self.guid.value.trim();
+        let (id, version) = split_arxiv_id(guid.trim_start_matches("oai:arXiv.org:"));
+        let announced = DateTime::parse_from_rfc2822(self.pub_date.trim())
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| ParserError::Date { value: self.pub_date.clone(), source: e })?;
+        // announcements happen at midnight US Eastern, date them by the day.
+        let announced = announced.date_naive().and_time(NaiveTime::MIN).and_utc();
+
+        // `arXiv:2501.01234v1 Announce Type: new Abstract: ...`
+        let description = clean(&self.description);
+        let summary = description.split_once("Abstract:")
+            .map(|(_, summary)| summary.trim().to_string())
+            .unwrap_or(description);
+        let categories: Vec<String> = self.categories.iter().map(|c| clean(c)).collect();
+        // the categories are not listed primary first, so the primary is only
+        // known when there is a single one.
+        let primary_category = match categories.as_slice() {
+            [category] => category.clone(),
+            _ => String::new()
+        };
+
+        Ok(ArxivResult {
+            source: Source::Arxiv,
+            id,
+            version: version.unwrap_or(1),
+            index,
+            title: clean(&self.title),
+            summary,
+            authors: split_authors(&self.creator),
+            published: announced,
+            updated: announced,
+            link: self.link.trim().to_string(),
+            primary_category,
+            categories,
+            comment: None,
+            journal_ref: self.journal_ref.map(|value| clean(&value)).filter(|v| !v.is_empty()),
+            doi: self.doi.map(|value| clean(&value)).filter(|v| !v.is_empty()),
+            announce_type: self.announce_type.as_deref().and_then(AnnounceType::from_name)
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RssDocument {
+    channel: RssChannel
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RssChannel {
+    #[serde(rename = "item")]
+    items: Vec<RssItem>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RssItem {
+    title: String,
+    link: String,
+    description: String,
+    guid: GuidField,
+    #[serde(rename = "category")]
+    categories: Vec<String>,
+    #[serde(rename = "pubDate")]
+    pub_date: String,
+    // namespaced `arxiv:` and `dc:` elements are matched by their local name.
+    announce_type: Option<String>,
+    creator: String,
+    #[serde(rename = "journal_reference")]
+    journal_ref: Option<String>,
+    #[serde(rename = "DOI")]
+    doi: Option<String>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GuidField {
+    #[serde(rename = "$text")]
+    value: String
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::model::Author;
+
+    const FEED: &str = r#"<?xml version='1.0' encoding='UTF-8'?>
+    <rss xmlns:arxiv="http://arxiv.org/schemas/atom" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:atom="http://www.w3.org/2005/Atom" version="2.0">
+      <channel>
+        <title>cs.CL, cs.AI updates on arXiv.org</title>
+        <link>http://rss.arxiv.org/rss/cs.CL+cs.AI</link>
+        <description>cs.CL, cs.AI updates on the arXiv.org e-print archive.</description>
+        <atom:link href="https://rss.arxiv.org/atom/cs.CL+cs.AI" rel="self" type="application/rss+xml"/>
+        <pubDate>Fri, 03 Jan 2025 00:00:00 -0500</pubDate>
+        <skipDays><day>Saturday</day><day>Sunday</day></skipDays>
+        <item>
+          <title>A Paper Title</title>
+          <link>https://arxiv.org/abs/2501.01234</link>
+          <description>arXiv:2501.01234v1 Announce Type: cross
+    Abstract: An   abstract.</description>
+          <guid isPermaLink="false">oai:arXiv.org:2501.01234v1</guid>
+          <category>cs.AI</category>
+          <category>cs.CL</category>
+          <pubDate>Fri, 03 Jan 2025 00:00:00 -0500</pubDate>
+          <arxiv:announce_type>cross</arxiv:announce_type>
+          <dc:rights>http://creativecommons.org/licenses/by/4.0/</dc:rights>
+          <dc:creator>Jane Doe, John Roe</dc:creator>
+        </item>
+        <item>
+          <title>A Paper Title</title>
+          <link>https://arxiv.org/abs/2501.01234</link>
+          <description>arXiv:2501.01234v1 Announce Type: new
+    Abstract: An   abstract.</description>
+          <guid isPermaLink="false">oai:arXiv.org:2501.01234v1</guid>
+          <category>cs.AI</category>
+          <category>cs.CL</category>
+          <pubDate>Fri, 03 Jan 2025 00:00:00 -0500</pubDate>
+          <arxiv:announce_type>new</arxiv:announce_type>
+          <dc:creator>Jane Doe, John Roe</dc:creator>
+        </item>
+        <item>
+          <title>An Older Paper</title>
+          <link>https://arxiv.org/abs/2412.05678</link>
+          <description>arXiv:2412.05678v3 Announce Type: replace-cross
+    Abstract: Revised abstract.</description>
+          <guid isPermaLink="false">oai:arXiv.org:2412.05678v3</guid>
+          <category>cs.CL</category>
+          <pubDate>Fri, 03 Jan 2025 00:00:00 -0500</pubDate>
+          <arxiv:announce_type>replace-cross</arxiv:announce_type>
+          <dc:creator>Max Mustermann</dc:creator>
+          <arxiv:journal_reference>ACL 2025</arxiv:journal_reference>
+          <arxiv:DOI>10.1234/abcd</arxiv:DOI>
+        </item>
+      </channel>
+    </rss>"#;
+
+    #[test]
+    fn test_feed_parsing() {
+        let document: RssDocument = quick_xml::de::from_str(FEED).unwrap();
+        let results = parse_items(document.channel.items).unwrap();
+        assert_eq!(results.len(), 2);
+
+        let first = &results[0];
+        assert_eq!(first.id, "2501.01234");
+        assert_eq!(first.version, 1);
+        assert_eq!(first.summary, "An abstract.");
+        assert_eq!(first.authors, vec![
+            Author::new("Jane Doe".to_string(), vec![]),
+            Author::new("John Roe".to_string(), vec![])
+        ]);
+        assert_eq!(first.published, Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).unwrap());
+        assert_eq!(first.categories, vec!["cs.AI", "cs.CL"]);
+        assert_eq!(first.primary_category, "");
+        assert_eq!(first.announce_type, Some(AnnounceType::New));
+
+        let second = &results[1];
+        assert_eq!(second.id, "2412.05678");
+        assert_eq!(second.version, 3);
+        assert_eq!(second.index, 1);
+        assert_eq!(second.announce_type, Some(AnnounceType::ReplaceCross));
+        assert_eq!(second.primary_category, "cs.CL");
+        assert_eq!(second.journal_ref.as_deref(), Some("ACL 2025"));
+        assert_eq!(second.doi.as_deref(), Some("10.1234/abcd"));
+    }
+
+    #[test]
+    fn test_feed_url() {
+        let feed = RssFeed::default();
+        assert_eq!(feed.feed_url(), "https://rss.arxiv.org/rss/cs.CL+cs.AI+cs.LG+cs.MA");
+    }
+}
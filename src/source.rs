@@ -0,0 +1,196 @@
+use std::{env, sync::LazyLock};
+use async_trait::async_trait;
+use chrono::Utc;
+use regex::Regex;
+
+use crate::{
+    acl::AclAnthology,
+    biorxiv::BiorxivApi,
+    config::{
+        AclConfig, ArxivConfig, BiorxivConfig, OpenReviewConfig, PubmedConfig, SourceKind
+    },
+    model::{ArxivResult, DateRange, Source},
+    oai::{MetadataFormat, OaiHarvester},
+    openreview::OpenReview,
+    parser::{ArxivParser, ParserError},
+    pubmed::Pubmed,
+    rss::RssFeed
+};
+
+static INLINE_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]+>").unwrap());
+static WHITESPACE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+
+/// A server papers are fetched from.
+#[async_trait]
+pub trait PaperSource: Send + Sync {
+    fn source(&self) -> Source;
+
+    /// Range fetched by scheduled runs.
+    fn default_range(&self) -> DateRange;
+
+    /// Papers that appeared within `range`, what "appeared" means depends on
+    /// the source.
+    async fn fetch(&self, range: &DateRange) -> Result<Vec<ArxivResult>, ParserError>;
+
+    /// Papers by their identifier in this source.
+    async fn fetch_by_ids(&self, _ids: &[&str]) -> Result<Vec<ArxivResult>, ParserError> {
+        Err(ParserError::Unavailable(format!("{} cannot look up papers by id", self.source())))
+    }
+}
+
+/// Builds the source of `kind`. arXiv clients use `arxiv`, other sources read
+/// their own configuration from the environment.
+pub fn from_kind(kind: SourceKind, arxiv: &ArxivConfig) -> Box<dyn PaperSource> {
+    match kind {
+        SourceKind::Api => Box::new(ArxivParser::from_config(arxiv.clone())),
+        SourceKind::Oai => {
+            // OAI-PMH selects records by set and datestamp only, a query would
+            // be silently dropped.
+            assert!(arxiv.query.is_none(), "QUERY is not supported with SOURCE=oai");
+            // `OAI_METADATA_FORMAT` (arXiv, arXivRaw) is optional.
+            let format = env::var("OAI_METADATA_FORMAT")
+                .map(|name| MetadataFormat::from_name(&name)
+                    .unwrap_or_else(|| panic!("unknown OAI_METADATA_FORMAT: {}", name)))
+                .unwrap_or_default();
+            Box::new(OaiHarvester::from_config(arxiv.clone()).with_format(format))
+        },
+        SourceKind::Rss => Box::new(RssFeed::from_config(arxiv.clone())),
+        SourceKind::Biorxiv => Box::new(BiorxivApi::biorxiv(BiorxivConfig::from_env("BIORXIV"))),
+        SourceKind::Medrxiv => Box::new(BiorxivApi::medrxiv(BiorxivConfig::from_env("MEDRXIV"))),
+        SourceKind::Acl => Box::new(AclAnthology::from_config(AclConfig::from_env())),
+        SourceKind::OpenReview => Box::new(OpenReview::from_config(OpenReviewConfig::from_env())),
+        SourceKind::Pubmed => Box::new(Pubmed::from_config(PubmedConfig::from_env()))
+    }
+}
+
+/// Sources listed in `SOURCE`.
+pub fn from_env(arxiv: &ArxivConfig) -> Vec<Box<dyn PaperSource>> {
+    SourceKind::from_env().into_iter()
+        .map(|kind| from_kind(kind, arxiv))
+        .collect()
+}
+
+// the previous UTC day, complete by the time scheduled runs start.
+pub(crate) fn previous_day() -> DateRange {
+    DateRange::from_offset(Utc::now(), 0)
+}
+
+// Trims and collapses whitespace runs, e.g. the line breaks of XML text.
+pub(crate) fn clean(text: &str) -> String {
+    WHITESPACE.replace_all(text.trim(), " ").to_string()
+}
+
+// `MalformedEntry` for results without a title or an abstract, which leave
+// the summarizer nothing to work with.
+pub(crate) fn require_text(result: ArxivResult) -> Result<ArxivResult, ParserError> {
+    if result.title.is_empty() || result.summary.is_empty() {
+        return Err(ParserError::MalformedEntry(format!(
+            "{} has no {}", result.id, if result.title.is_empty() { "title" } else { "abstract" })));
+    }
+    Ok(result)
+}
+
+// `None` for malformed entries, which are skipped with a warning rather than
+// failing the whole response.
+pub(crate) fn skip_malformed(
+    result: Result<ArxivResult, ParserError>
+) -> Result<Option<ArxivResult>, ParserError> {
+    match result {
+        Ok(result) => Ok(Some(result)),
+        Err(ParserError::MalformedEntry(message)) => {
+            eprintln!("Warning: skipping entry: {}", message);
+            Ok(None)
+        },
+        Err(e) => Err(e)
+    }
+}
+
+// Drops the markup inside a set of elements, e.g. `<i>` or `<fixed-case>` in
+// titles, which the XML deserializer cannot read into plain strings. Built
+// once per provider, typically in a `LazyLock`.
+pub(crate) struct InlineMarkup {
+    element: Regex
+}
+
+impl InlineMarkup {
+    pub(crate) fn new(elements: &[&str]) -> Self {
+        let names = elements.join("|");
+        InlineMarkup {
+            element: Regex::new(&format!(r"(?s)(<(?:{0})(?:\s[^>]*)?>)(.*?)(</(?:{0})>)", names))
+                .unwrap()
+        }
+    }
+
+    pub(crate) fn strip(&self, xml: &str) -> String {
+        self.element.replace_all(xml, |caps: &regex::Captures| {
+            format!("{}{}{}", &caps[1], INLINE_TAG.replace_all(&caps[2], ""), &caps[3])
+        }).to_string()
+    }
+}
+
+#[async_trait]
+impl PaperSource for ArxivParser {
+    fn source(&self) -> Source {
+        Source::Arxiv
+    }
+
+    fn default_range(&self) -> DateRange {
+        ArxivParser::default_range(self)
+    }
+
+    async fn fetch(&self, range: &DateRange) -> Result<Vec<ArxivResult>, ParserError> {
+        self.get_arxiv_results(range).await
+    }
+
+    async fn fetch_by_ids(&self, ids: &[&str]) -> Result<Vec<ArxivResult>, ParserError> {
+        ArxivParser::fetch_by_ids(self, ids).await
+    }
+}
+
+#[async_trait]
+impl PaperSource for OaiHarvester {
+    fn source(&self) -> Source {
+        Source::Arxiv
+    }
+
+    fn default_range(&self) -> DateRange {
+        OaiHarvester::default_range(self)
+    }
+
+    async fn fetch(&self, range: &DateRange) -> Result<Vec<ArxivResult>, ParserError> {
+        self.get_arxiv_results(range).await
+    }
+
+    async fn fetch_by_ids(&self, ids: &[&str]) -> Result<Vec<ArxivResult>, ParserError> {
+        OaiHarvester::fetch_by_ids(self, ids).await
+    }
+}
+
+#[async_trait]
+impl PaperSource for RssFeed {
+    fn source(&self) -> Source {
+        Source::Arxiv
+    }
+
+    fn default_range(&self) -> DateRange {
+        RssFeed::default_range(self)
+    }
+
+    async fn fetch(&self, range: &DateRange) -> Result<Vec<ArxivResult>, ParserError> {
+        self.get_arxiv_results(range).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_markup() {
+        let xml = r#"<paper><title>A <fixed-case>BERT</fixed-case> Study</title><abstract Label="A">x<sup>2</sup> &amp; <i>y</i></abstract><url>2024.acl-long.1</url></paper>"#;
+        assert_eq!(
+            InlineMarkup::new(&["title", "abstract"]).strip(xml),
+            r#"<paper><title>A BERT Study</title><abstract Label="A">x2 &amp; y</abstract><url>2024.acl-long.1</url></paper>"#
+        );
+    }
+}
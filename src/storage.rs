@@ -1,5 +1,5 @@
 use std::{
-    fs::File, 
+    fs::{self, File}, 
     io::{self, Write}, 
     path::Path,
     error::Error as StdError,
@@ -8,15 +8,21 @@ use std::{
 };
 use aws_sdk_s3::{
     error::SdkError, 
-    operation::put_object::{PutObjectError, PutObjectOutput}, 
+    operation::{
+        delete_object::DeleteObjectError,
+        get_object::GetObjectError,
+        head_object::HeadObjectError,
+        put_object::{PutObjectError, PutObjectOutput}
+    }, 
     primitives::ByteStream, 
     primitives::ByteStreamError,
     Client as S3Client
 };
+use serde::Serialize;
 use serde_json::{self, Error as JsonError};
 use uuid::Uuid;
 
-use crate::model::ProcessedResult;
+use crate::model::{FailedResult, ProcessedResult};
 
 // Utils to store (temporary) files on local device.
 // When using with AWS Lambda, these local files (in /tmp) will automatically be
@@ -26,24 +32,66 @@ struct Formatter;
 
 impl Formatter {
     fn to_readme(data: &ProcessedResult) -> Result<String, JsonError> {
-        Ok(format!("### {}\n_{}_<br/>\n{}<br/>\n_Published: {}_, [{}]({})\n\n",
+        let authors = data.authors.iter()
+            .map(|author| author.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let venue = data.journal_ref.as_ref()
+            .map(|venue| format!(", {}", venue))
+            .unwrap_or_default();
+        // unknown for some sources.
+        let category = if data.primary_category.is_empty() {
+            String::new()
+        } else {
+            format!(" `{}`", data.primary_category)
+        };
+        let update = if data.is_update {
+            format!(" _(updated, v{})_", data.version)
+        } else {
+            String::new()
+        };
+        Ok(format!("### {}{}\n_{}_<br/>\n{}<br/>\n_Published: {}_, {}{}{}, [{}]({})\n\n",
             data.title,
-            data.authors.join(", "),
-            data.summary,
+            update,
+            authors,
+            Self::summary_markdown(data),
             data.published.format("%Y.%m.%d"),
+            data.source,
+            category,
+            venue,
             data.link, data.link
         ))
     }
 
-    fn to_jsonl(data: &ProcessedResult) -> Result<String, JsonError> {
+    // the TL;DR with the other sections of structured summaries.
+    fn summary_markdown(data: &ProcessedResult) -> String {
+        let Some(structured) = &data.structured else {
+            return data.summary.clone();
+        };
+        let mut text = format!("**TL;DR** {}<br/>\n", structured.tldr);
+        structured.contributions.iter()
+            .for_each(|item| text.push_str(&format!("- {}\n", item)));
+        text.push_str(&format!("\n**Method** {}<br/>\n**Results** {}", structured.method, structured.results));
+        if !structured.limitations.is_empty() {
+            text.push_str(&format!("<br/>\n**Limitations** {}", structured.limitations));
+        }
+        let tags = structured.tags.iter()
+            .map(|tag| format!("`{}`", tag))
+            .collect::<Vec<_>>()
+            .join(" ");
+        text.push_str(&format!("<br/>\n{}", tags));
+        text
+    }
+
+    fn to_jsonl<T: Serialize>(data: &T) -> Result<String, JsonError> {
         let jstring = serde_json::to_string(data)?;
         Ok(format!("{}\n", jstring))
     }
 }
 
-fn save_arxiv_as_file<F>(fname: &str, op: F, data: &Vec<ProcessedResult>) -> io::Result<()>
+fn save_arxiv_as_file<T, F>(fname: &str, op: F, data: &[T]) -> io::Result<()>
 where
-    F: Fn(&ProcessedResult) -> Result<String, JsonError>
+    F: Fn(&T) -> Result<String, JsonError>
 {
     let mut file = File::create(fname)?;
     data.iter()
@@ -87,7 +135,7 @@ impl S3Storage {
         &self,
         bucket: &str,
         key: &str,
-        data: &Vec<ProcessedResult>
+        data: &[ProcessedResult]
     ) -> Result<PutObjectOutput, StorageError> {
         let tmp_file = self.get_fname("readme", "md");
         save_arxiv_as_file(&tmp_file, Formatter::to_readme, data)?;
@@ -98,13 +146,62 @@ impl S3Storage {
         &self,
         bucket: &str,
         key: &str,
-        data: &Vec<ProcessedResult>
+        data: &[ProcessedResult]
     ) -> Result<PutObjectOutput, StorageError> {
         let tmp_file = self.get_fname("tmp", "jsonl");
         save_arxiv_as_file(&tmp_file, Formatter::to_jsonl, data)?;
         self.upload(bucket, key, &tmp_file).await
     }
 
+    pub async fn upload_failures_as_jsonl(
+        &self,
+        bucket: &str,
+        key: &str,
+        data: &[FailedResult]
+    ) -> Result<PutObjectOutput, StorageError> {
+        let tmp_file = self.get_fname("failed", "jsonl");
+        save_arxiv_as_file(&tmp_file, Formatter::to_jsonl, data)?;
+        self.upload(bucket, key, &tmp_file).await
+    }
+
+    pub async fn upload_text(
+        &self,
+        bucket: &str,
+        key: &str,
+        text: &str
+    ) -> Result<PutObjectOutput, StorageError> {
+        let tmp_file = self.get_fname("text", "txt");
+        fs::write(&tmp_file, text)?;
+        self.upload(bucket, key, &tmp_file).await
+    }
+
+    /// Contents of `key`, `None` if it does not exist.
+    pub async fn download_text(&self, bucket: &str, key: &str) -> Result<Option<String>, StorageError> {
+        let output = match self.client.get_object().bucket(bucket).key(key).send().await {
+            Ok(output) => output,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => return Ok(None),
+            Err(e) => return Err(StorageError::from(e))
+        };
+        let bytes = output.body.collect().await?.into_bytes();
+        String::from_utf8(bytes.to_vec())
+            .map(Some)
+            .map_err(|e| StorageError::new(&format!("{} is not UTF-8: {}", key, e)))
+    }
+
+    pub async fn exists(&self, bucket: &str, key: &str) -> Result<bool, StorageError> {
+        match self.client.head_object().bucket(bucket).key(key).send().await {
+            Ok(_) => Ok(true),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(false),
+            Err(e) => Err(StorageError::from(e))
+        }
+    }
+
+    /// Removes `key`, succeeding as well when it does not exist.
+    pub async fn delete(&self, bucket: &str, key: &str) -> Result<(), StorageError> {
+        self.client.delete_object().bucket(bucket).key(key).send().await?;
+        Ok(())
+    }
+
     async fn upload(
         &self,
         bucket: &str,
@@ -165,6 +262,24 @@ impl From<SdkError<PutObjectError>> for StorageError {
     }
 }
 
+impl From<SdkError<GetObjectError>> for StorageError {
+    fn from(err: SdkError<GetObjectError>) -> Self {
+        StorageError::new(&format!("AWS SDK error: {}", err))
+    }
+}
+
+impl From<SdkError<DeleteObjectError>> for StorageError {
+    fn from(err: SdkError<DeleteObjectError>) -> Self {
+        StorageError::new(&format!("AWS SDK error: {}", err))
+    }
+}
+
+impl From<SdkError<HeadObjectError>> for StorageError {
+    fn from(err: SdkError<HeadObjectError>) -> Self {
+        StorageError::new(&format!("AWS SDK error: {}", err))
+    }
+}
+
 impl From<ByteStreamError> for StorageError {
     fn from(err: ByteStreamError) -> Self {
         StorageError::new(&format!("AWS SDK ByteStream error: {}", err))
@@ -0,0 +1,141 @@
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// Structured summaries, requested through the providers' schema support
+// (OpenAI JSON schema response format, Bedrock tool use) and validated here,
+// since neither enforces lengths or non-empty fields.
+
+// name of the response format and of the Bedrock tool.
+pub const SCHEMA_NAME: &str = "paper_summary";
+pub const SCHEMA_DESCRIPTION: &str = "Structured summary of a research paper for a newsletter.";
+
+const MAX_CONTRIBUTIONS: usize = 5;
+const MAX_TAGS: usize = 8;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct StructuredSummary {
+    // one line.
+    pub tldr: String,
+    pub contributions: Vec<String>,
+    pub method: String,
+    pub results: String,
+    // empty when the abstract mentions none.
+    #[serde(default)]
+    pub limitations: String,
+    // lowercase keywords.
+    pub tags: Vec<String>
+}
+
+/// JSON schema of `StructuredSummary`, in the subset accepted by OpenAI's
+/// strict mode: every property required, no additional properties.
+pub fn schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "tldr": {
+                "type": "string",
+                "description": "One sentence on a single line, at most 30 words."
+            },
+            "contributions": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "The key contributions, 1 to 5 short items."
+            },
+            "method": {
+                "type": "string",
+                "description": "How the research works, in one or two sentences."
+            },
+            "results": {
+                "type": "string",
+                "description": "The main findings, in one or two sentences."
+            },
+            "limitations": {
+                "type": "string",
+                "description": "Limitations stated in the abstract, empty if there are none."
+            },
+            "tags": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "3 to 8 lowercase keyword tags."
+            }
+        },
+        "required": ["tldr", "contributions", "method", "results", "limitations", "tags"],
+        "additionalProperties": false
+    })
+}
+
+impl StructuredSummary {
+    /// Parses and validates a model response, see `from_value`.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(text.trim())
+            .map_err(|e| format!("invalid JSON: {}", e))?;
+        Self::from_value(value)
+    }
+
+    /// Checks the required fields and normalizes the lists: items are
+    /// trimmed, tags lowercased and deduplicated, and both capped.
+    pub fn from_value(value: Value) -> Result<Self, String> {
+        let mut summary: StructuredSummary = serde_json::from_value(value)
+            .map_err(|e| format!("does not match the schema: {}", e))?;
+        summary.tldr = summary.tldr.trim().to_string();
+        if summary.tldr.is_empty() || summary.tldr.contains('\n') {
+            return Err(String::from("tldr must be a single non-empty line"));
+        }
+        summary.contributions = summary.contributions.iter()
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .take(MAX_CONTRIBUTIONS)
+            .collect();
+        if summary.contributions.is_empty() {
+            return Err(String::from("contributions must not be empty"));
+        }
+        summary.method = summary.method.trim().to_string();
+        summary.results = summary.results.trim().to_string();
+        if summary.method.is_empty() || summary.results.is_empty() {
+            return Err(String::from("method and results must not be empty"));
+        }
+        summary.limitations = summary.limitations.trim().to_string();
+        let mut seen: HashSet<String> = HashSet::new();
+        summary.tags = summary.tags.iter()
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty() && seen.insert(tag.clone()))
+            .take(MAX_TAGS)
+            .collect();
+        if summary.tags.is_empty() {
+            return Err(String::from("tags must not be empty"));
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        let summary = StructuredSummary::from_json(r#"{
+            "tldr": " Sparse attention in linear time. ",
+            "contributions": ["A new kernel", " ", "Open weights"],
+            "method": "Hashes queries into buckets.",
+            "results": "2x faster at equal perplexity.",
+            "limitations": "",
+            "tags": ["Attention", "efficiency", "attention"]
+        }"#).unwrap();
+        assert_eq!(summary.tldr, "Sparse attention in linear time.");
+        assert_eq!(summary.contributions, vec!["A new kernel", "Open weights"]);
+        assert_eq!(summary.tags, vec!["attention", "efficiency"]);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(StructuredSummary::from_json("A plain text summary.").is_err());
+        // missing fields.
+        assert!(StructuredSummary::from_json(r#"{"tldr": "x"}"#).is_err());
+        let err = StructuredSummary::from_value(json!({
+            "tldr": "x", "contributions": [], "method": "m", "results": "r", "tags": ["t"]
+        })).unwrap_err();
+        assert_eq!(err, "contributions must not be empty");
+    }
+}